@@ -0,0 +1,230 @@
+//! Components: `(@Path prop:value ... (:slot ...) children)`
+//!
+//! The props and slots become the fields of a struct literal of the component type, so rustc checks them like any
+//! other struct literal, and the remaining children are collected in an `Html` value passed to `Component::render`.
+//! With `..name` the slots are fields of the `name` field instead, which is filled from `Default` first.
+use quote::{quote, ToTokens};
+use syn::{parse::Parse, Token};
+
+use crate::{BracedExpr, Html, Node};
+
+/// `(@Path prop:value prop .. (:slot ...) children)`
+pub(crate) struct Component {
+    path: syn::Path,
+    props: Vec<Prop>,
+    rest: Option<Rest>,
+    slots: Vec<Slot>,
+    children: Html,
+}
+
+/// `(:name nodes)`, content given to the field `name` of the component
+struct Slot {
+    name: syn::Ident,
+    content: Html,
+}
+
+/// A prop: `name:value`, or `name` alone for a variable of the same name
+struct Prop {
+    name: syn::Ident,
+    val: Option<PropVal>,
+}
+
+enum PropVal {
+    Lit(syn::Lit),
+    Expr(BracedExpr),
+}
+
+/// The `..` ending the props
+enum Rest {
+    /// `..`, the fields left out are filled from `Default`
+    Default(Token![..]),
+    /// `..name`, the slots are the fields of the field `name`, whose other fields keep their `Default` value
+    Slots(syn::Ident),
+}
+
+impl Component {
+    /// Checks whether the next token tree is a parenthesized group starting with `@` and a path
+    pub(crate) fn peek(input: syn::parse::ParseStream) -> bool {
+        input
+            .cursor()
+            .group(proc_macro2::Delimiter::Parenthesis)
+            .and_then(|(inner, _, _)| inner.punct())
+            .filter(|(punct, _)| punct.as_char() == '@')
+            .is_some_and(|(_, rest)| {
+                rest.ident().is_some()
+                    || rest
+                        .punct()
+                        .is_some_and(|(punct, _)| punct.as_char() == ':')
+            })
+    }
+}
+
+impl Parse for Component {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let content;
+        syn::parenthesized!(content in input);
+        content.parse::<Token![@]>()?;
+        let path = content.parse()?;
+        let mut props = vec![];
+        while content.peek(syn::Ident) {
+            let name = content.parse()?;
+            let val = match content.peek(Token![:]) {
+                true => {
+                    content.parse::<Token![:]>()?;
+                    Some(content.parse()?)
+                }
+                false => None,
+            };
+            props.push(Prop { name, val });
+        }
+        let rest = match content.parse::<Option<Token![..]>>()? {
+            Some(_) if content.peek(syn::Ident) => Some(Rest::Slots(content.parse()?)),
+            dots => dots.map(Rest::Default),
+        };
+        if content.peek(Token![=]) {
+            content.parse::<Token![=]>()?;
+            if content.is_empty() {
+                return Err(content.error("expected content after `=`"));
+            }
+        }
+        let mut slots = vec![];
+        let mut children = vec![];
+        while !content.is_empty() {
+            match Slot::peek(&content) {
+                true => slots.push(content.parse()?),
+                false => children.push(content.parse::<Node>()?),
+            }
+        }
+        Ok(Self {
+            path,
+            props,
+            rest,
+            slots,
+            children: Html(children),
+        })
+    }
+}
+
+impl Slot {
+    /// Checks whether the next token tree is a parenthesized group starting with `:` and a name
+    fn peek(input: syn::parse::ParseStream) -> bool {
+        input
+            .cursor()
+            .group(proc_macro2::Delimiter::Parenthesis)
+            .and_then(|(inner, _, _)| inner.punct())
+            .filter(|(punct, _)| punct.as_char() == ':')
+            .is_some_and(|(_, rest)| rest.ident().is_some())
+    }
+}
+
+impl Parse for Slot {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let content;
+        syn::parenthesized!(content in input);
+        content.parse::<Token![:]>()?;
+        let name = content.parse()?;
+        if content.peek(Token![=]) {
+            content.parse::<Token![=]>()?;
+        }
+        let mut nodes = vec![];
+        while !content.is_empty() {
+            nodes.push(content.parse()?);
+        }
+        Ok(Self {
+            name,
+            content: Html(nodes),
+        })
+    }
+}
+
+impl Slot {
+    /// The slot content converted with `Into`, so slots can be `Html` or `Option<Html>` fields
+    fn value(&self) -> proc_macro2::TokenStream {
+        let content = &self.content;
+        let content = match content.0.is_empty() {
+            true => quote!(::hteaml::Html::default()),
+            false => quote!({ #content }),
+        };
+        quote!(::core::convert::Into::into(#content))
+    }
+}
+
+impl ToTokens for Slot {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        let name = &self.name;
+        let value = self.value();
+        quote!(#name: #value).to_tokens(tokens)
+    }
+}
+
+impl Parse for PropVal {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        if input.peek(syn::token::Brace) {
+            input.parse().map(PropVal::Expr)
+        } else if input.peek(syn::Lit) {
+            input.parse().map(PropVal::Lit)
+        } else {
+            Err(input.error(
+                "expected a literal or a { braced Rust expression } as the value of the prop",
+            ))
+        }
+    }
+}
+
+impl ToTokens for Prop {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        let name = &self.name;
+        match &self.val {
+            Some(PropVal::Lit(lit)) => quote!(#name: #lit),
+            Some(PropVal::Expr(expr)) => quote!(#name: #expr),
+            None => quote!(#name),
+        }
+        .to_tokens(tokens)
+    }
+}
+
+/// Only `..` is written in the struct literal, `..name` is filled separately
+impl ToTokens for Rest {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        if let Rest::Default(dots) = self {
+            quote!(#dots ::core::default::Default::default()).to_tokens(tokens)
+        }
+    }
+}
+
+impl ToTokens for Component {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        let Self {
+            path,
+            props,
+            rest,
+            slots,
+            children,
+        } = self;
+        let children = match children.0.is_empty() {
+            true => quote!(::hteaml::Html::default()),
+            false => quote!({ #children }),
+        };
+        if let Some(Rest::Slots(field)) = rest {
+            let component = proc_macro2::Ident::new("component", proc_macro2::Span::mixed_site());
+            let names = slots.iter().map(|slot| &slot.name);
+            let values = slots.iter().map(Slot::value);
+            return quote!({
+                let mut #component = #path {
+                    #(#props,)*
+                    #field: ::core::default::Default::default(),
+                };
+                #(#component.#field.#names = #values;)*
+                ::hteaml::Component::render(#component, #children)
+            })
+            .to_tokens(tokens);
+        }
+        quote! {
+            ::hteaml::Component::render(
+                #path { #(#props,)* #(#slots,)* #rest },
+                #children,
+            )
+        }
+        .to_tokens(tokens)
+    }
+}
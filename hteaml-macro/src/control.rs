@@ -0,0 +1,217 @@
+//! Control flow nodes: `@if`, `@for`, `@match` and `@let`
+//!
+//! They expand to the matching Rust statements, with the nodes in their bodies pushed to the same [`Sink`] as the
+//! nodes around them, so no intermediate `Html` values are built.
+use quote::{quote, ToTokens};
+use syn::{parse::Parse, Token};
+
+use crate::{BracedExpr, Node, Sink};
+
+/// `@if {cond} node`, followed by any number of `@else if {cond} node` and an optional `@else node`
+///
+/// The condition can also be a pattern match: `@if let Some(x) = {expr} node`.
+pub(crate) struct If {
+    cond: Cond,
+    then: Box<Node>,
+    otherwise: Option<Box<Node>>,
+}
+
+enum Cond {
+    Expr(BracedExpr),
+    Let(syn::Pat, BracedExpr),
+}
+
+impl Parse for If {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        input.parse::<Token![@]>()?;
+        If::parse_after_at(input)
+    }
+}
+
+impl If {
+    /// Parse the rest of an `@if` once the `@` is consumed, which `@else if` doesn't have
+    fn parse_after_at(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        input.parse::<Token![if]>()?;
+        let cond = match input.peek(Token![let]) {
+            true => {
+                input.parse::<Token![let]>()?;
+                let pat = syn::Pat::parse_multi_with_leading_vert(input)?;
+                input.parse::<Token![=]>()?;
+                Cond::Let(pat, braced(input, "@if let pattern = {expr}")?)
+            }
+            false => Cond::Expr(braced(input, "@if {condition}")?),
+        };
+        let then = Box::new(input.parse()?);
+        let mut otherwise = None;
+        if input.peek(Token![@]) && input.peek2(Token![else]) {
+            input.parse::<Token![@]>()?;
+            input.parse::<Token![else]>()?;
+            let branch = match input.peek(Token![if]) {
+                true => Node::If(If::parse_after_at(input)?),
+                false => input.parse()?,
+            };
+            otherwise = Some(Box::new(branch));
+        }
+        Ok(Self {
+            cond,
+            then,
+            otherwise,
+        })
+    }
+
+    pub(crate) fn expand(&self, sink: &Sink) -> proc_macro2::TokenStream {
+        let cond = match &self.cond {
+            Cond::Expr(e) => e.to_token_stream(),
+            Cond::Let(pat, e) => quote!(let #pat = #e),
+        };
+        let then = sink.push(&self.then);
+        let otherwise = self.otherwise.as_deref().map(|branch| match branch {
+            Node::If(i) => {
+                let i = i.expand(sink);
+                quote!(else #i)
+            }
+            node => {
+                let node = sink.push(node);
+                quote!(else { #node })
+            }
+        });
+        quote! {
+            if #cond {
+                #then
+            } #otherwise
+        }
+    }
+}
+
+/// `@for pattern in {iter} node`
+pub(crate) struct For {
+    pat: syn::Pat,
+    iter: BracedExpr,
+    body: Box<Node>,
+}
+
+impl Parse for For {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        input.parse::<Token![@]>()?;
+        input.parse::<Token![for]>()?;
+        let pat = syn::Pat::parse_multi_with_leading_vert(input)?;
+        input.parse::<Token![in]>()?;
+        Ok(Self {
+            pat,
+            iter: braced(input, "@for pattern in {iter}")?,
+            body: Box::new(input.parse()?),
+        })
+    }
+}
+
+impl For {
+    pub(crate) fn expand(&self, sink: &Sink) -> proc_macro2::TokenStream {
+        let Self { pat, iter, body } = self;
+        let body = sink.push(body);
+        quote! {
+            for #pat in #iter {
+                #body
+            }
+        }
+    }
+}
+
+/// `@match {expr} { pattern => node, ... }`, arms can have guards like in Rust: `pattern if cond => node`
+pub(crate) struct Match {
+    expr: BracedExpr,
+    arms: Vec<Arm>,
+}
+
+struct Arm {
+    pat: syn::Pat,
+    guard: Option<syn::Expr>,
+    body: Node,
+}
+
+impl Parse for Match {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        input.parse::<Token![@]>()?;
+        input.parse::<Token![match]>()?;
+        let expr = braced(input, "@match {expr}")?;
+        let content;
+        syn::braced!(content in input);
+        let mut arms = vec![];
+        while !content.is_empty() {
+            let pat = syn::Pat::parse_multi_with_leading_vert(&content)?;
+            let guard = match content.peek(Token![if]) {
+                true => {
+                    content.parse::<Token![if]>()?;
+                    Some(content.parse()?)
+                }
+                false => None,
+            };
+            content.parse::<Token![=>]>()?;
+            let body = content.parse()?;
+            if !content.is_empty() {
+                content.parse::<Token![,]>()?;
+            }
+            arms.push(Arm { pat, guard, body });
+        }
+        Ok(Self { expr, arms })
+    }
+}
+
+impl Match {
+    pub(crate) fn expand(&self, sink: &Sink) -> proc_macro2::TokenStream {
+        let expr = &self.expr;
+        let arms = self.arms.iter().map(|Arm { pat, guard, body }| {
+            let guard = guard.as_ref().map(|g| quote!(if #g));
+            let body = sink.push(body);
+            quote!(#pat #guard => { #body })
+        });
+        quote! {
+            match #expr {
+                #(#arms)*
+            }
+        }
+    }
+}
+
+/// `@let pattern = expr;`, the binding is visible to the nodes after it
+pub(crate) struct Let {
+    pat: syn::Pat,
+    ty: Option<syn::Type>,
+    expr: syn::Expr,
+}
+
+impl Parse for Let {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        input.parse::<Token![@]>()?;
+        input.parse::<Token![let]>()?;
+        let pat = syn::Pat::parse_single(input)?;
+        let ty = match input.peek(Token![:]) {
+            true => {
+                input.parse::<Token![:]>()?;
+                Some(input.parse()?)
+            }
+            false => None,
+        };
+        input.parse::<Token![=]>()?;
+        let expr = input.parse()?;
+        input.parse::<Token![;]>()?;
+        Ok(Self { pat, ty, expr })
+    }
+}
+
+impl ToTokens for Let {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        let Self { pat, ty, expr } = self;
+        let ty = ty.as_ref().map(|ty| quote!(: #ty));
+        quote!(let #pat #ty = #expr;).to_tokens(tokens)
+    }
+}
+
+/// Parse a `{ braced Rust expression }`, explaining where it is expected if it's missing
+fn braced(input: syn::parse::ParseStream, expected: &str) -> syn::Result<BracedExpr> {
+    match input.peek(syn::token::Brace) {
+        true => input.parse(),
+        false => Err(input.error(format!(
+            "expected a {{ braced Rust expression }}, as in `{expected}`"
+        ))),
+    }
+}
@@ -8,25 +8,236 @@ use syn::{
     Token,
 };
 
+mod component;
+mod control;
+
 /// The procedural macro for writing HTML using the alternative Lisp-like syntax
 ///
 /// ## Syntax
 /// The following are the basic syntax concepts that the user must grasp to use the macro correctly
 /// ### Tags
-/// `(tag key:val = "content")` is the equivalent of `<tag key="val">content</tag>`. If the equals sign along with the content part is omitted
-/// the tag  is assumed to be a self-closing tag: `(br)` would render to `<br>`. Attributes can be single keys without values: `(tag attr = "content")` -> `<tag attr>content</tag>`.
-/// Attributes can of course be omitted completely as seen in the self-closing tag example.
+/// `(tag key:val = "content")` is the equivalent of `<tag key="val">content</tag>`. The equals sign along with the content part can be omitted
+/// for tags without content: `(div)` renders to `<div></div>`. HTML5 void elements are written without an end tag, so `(br)` renders to `<br>`,
+/// and giving a void element content (e.g. `(br = "text")`) is a compile error.
+/// Attributes can be single keys without values: `(tag attr = "content")` -> `<tag attr>content</tag>`.
+/// Attributes can of course be omitted completely as seen in the void element example.
+///
+/// ```compile_fail
+/// # use hteaml::hteaml;
+/// let html = hteaml!((br = "void elements cannot have content"));
+/// ```
+///
+/// ### Attribute names
+/// Attribute keys are written the way they appear in HTML: `(input type:checkbox data-id:{id} aria-label:"Select")`.
+/// A key is any run of names, Rust keywords included, joined by `-` or `:`, and can start with `@` or `:` as used by
+/// frontend frameworks: `(button @click:"open = true" :class:{classes} hx-get:"/items")`.
+///
+/// The **last** `:` of a run separates the key from the value, everything before it is the key. So
+/// `(form x-on:submit:"save()")` sets `x-on:submit` and `(use xlink:href:"#icon")` sets `xlink:href`,
+/// while `(a href:home)` sets `href` to `home`. A key containing `:` therefore always needs a value; keys that
+/// don't fit these rules, such as `x-on:submit.prevent`, can be quoted: `(form "x-on:submit.prevent":"save()")`.
+///
+/// ### Concatenated values
+/// An attribute value can be made of several string literals and expressions grouped in parentheses:
+/// `(a href:("/users/" {id} "/edit"))` or `(button class:("btn btn-" {kind}))`. The parts are written into the attribute
+/// when rendered, without being joined into a `String` first, and each must implement `Into<AttrValue>`.
+///
+/// ### Optional and boolean attributes
+/// An attribute whose value is an `Option` is left out when it is `None`: `(a href:{link})`.
+/// Boolean attributes such as `disabled`, `checked` or `selected` are written as `key?:{cond}`, which adds the key without
+/// a value when `cond` is true and leaves it out otherwise: `(input type:checkbox checked?:{done} value:{maybe_val})`.
+/// A plain `key:{cond}` does the same for HTML boolean attributes, and writes the `bool` as `true` or `false` for other
+/// keys, as used by `aria-*` attributes.
+///
+/// ### Inline styles
+/// A `style` attribute can be written as a map of CSS declarations: `(p style:{ color: red, margin-top: {top} "px" })`.
+/// Values are written like attribute values, with bare names such as `red` and numbers such as `4px` or `1.5rem` taken
+/// as text. Units starting with `e`, like `em`, read as an exponent to Rust and have to be quoted: `"2em"`.
+/// Declarations keep their order, `None` values leave the declaration out and the whole attribute is escaped when
+/// rendered, see `Style`.
+///
+/// ### Spreading attributes
+/// `..{attrs}` adds every key and value pair of `attrs`, which can be any iterator of pairs such as a `Vec` or a map:
+/// `(button type:button ..{extra} = "Go")`. The pairs accept the same keys and values as other attributes.
+///
+/// Attributes are applied from left to right and the last value of a key wins, keeping the position where the key
+/// first appeared. So `(button type:button ..{extra})` lets `extra` override `type`, while
+/// `(button ..{extra} type:button)` doesn't. A `None` value, or `false` for a boolean attribute, removes the key
+/// instead. Classes are merged, see `Tag::attrs`.
+///
+/// ### Class lists
+/// Classes can be toggled with a list of classes in brackets, each optionally followed by `=> cond`:
+/// `(button class:["btn", "btn-primary" => is_primary, {extra}])`. Classes whose condition is false are dropped
+/// along with duplicates, and the `class` attribute is left out if no class is left, see `ClassList`.
+/// Names such as `btn-primary` can also be written without quotes.
+///
+/// ### Class and id shorthand
+/// Classes and an id can be written right after the tag name as in CSS selectors: `(div.card.is-active #main = "...")`
+/// renders to `<div class="card is-active" id="main">...</div>`. The shorthand classes are merged with a `class:`
+/// attribute into a single `class` attribute, and a tag can only have one `#id`.
+///
+/// Since Rust 2021 reserves `name#` as a prefix, `#id` must be separated from the name before it by a space:
+/// `(div #main.card)` or `(div.card #main)`.
 ///
 /// **Nesting:** Tags can be nested `(tag attr:val (tag2 attr:val = "content"))`. The `=` equals sign is optional while nesting tags. Multiple tags can be nested
 /// within a single tag: `(tag (tag2) (tag3))` is the same as `<tag><tag2><tag3></tag>`.
 ///
+/// ### Text
+/// String literals are not limited to the content of a tag, they can be used at the top level too:
+/// `hteaml!("Hello, " (b = {name}) "!")` is a fragment of text around a `<b>` tag.
+///
 /// ### Rust Expressions
 /// The macro allows you to use the usual Rust code in all places using blocks: `{...}`.
 /// #### Expression rules
-/// - Expressions used in the top-level macro invocation must evaluate to a type that implements `Into<Html>`
-/// - Expressions used in within a tag must evaluate to a type that implements `Into<Str>`
-/// - Expressions used in the tag's content must evaluate to a type that implements `Into<Str>` or `Into<Html>`
+/// - Expressions used in the top-level macro invocation must evaluate to a type that implements `Into<Html>`, which includes strings
+/// - Expressions used as a tag name or attribute key must evaluate to a type that implements `IntoStr`
+/// - Expressions used as attribute values must evaluate to a type that implements `IntoAttr`, i.e. `Into<AttrValue>` or an `Option` of it
+/// - Expressions used in the tag's content must evaluate to a type that implements `Into<Content>`, or to an iterator
+///   (any `IntoIterator`) of items that implement `Into<Html>`: `(ul = {items.iter().map(|i| hteaml!((li = {i})))})`.
+///   `Option` values render nothing when they are `None`
+/// - Besides strings, attribute values and content accept numbers, `bool` and `char`, which are formatted straight into
+///   the output. Other `Display` types can be used by borrowing them with `Fmt::display(&value)`
 /// - A sequence of expressions can be writen as `{expr} {expr2}` as long as they follow the above rules
+///
+/// ### Control flow
+/// Conditionals, loops and local bindings can be used wherever a tag can, both at the top level and in the content of a tag.
+/// Like other Rust code, conditions and iterators are written in `{}` blocks. The body of each branch is a single tag,
+/// string, expression or nested control flow.
+/// - `@if {cond} (...)`, optionally followed by `@else if {cond} (...)` and `@else (...)`
+/// - `@if let Some(x) = {expr} (...)`
+/// - `@for x in {iter} (...)`
+/// - `@match {expr} { pattern => (...), pattern if guard => (...) }`
+/// - `@let x = expr;` binds `x` for the rest of the enclosing tag
+///
+/// They expand to the matching Rust statements that add the nodes straight to the enclosing tag, so the bodies can borrow
+/// from the loop variables without nested `hteaml!` calls.
+///
+/// ```
+/// use hteaml::{hteaml, Render};
+/// let items = ["tea", "cake"];
+/// let html = hteaml! {
+///     (ul
+///         @for (i, item) in {items.iter().enumerate()} (li id:{i} = {*item})
+///         @if {items.is_empty()} (li = "nothing")
+///     )
+/// };
+/// assert_eq!(html.render(), Ok(r#"<ul><li id="0">tea</li><li id="1">cake</li></ul>"#.into()));
+/// ```
+///
+/// ### Components
+/// Types implementing `Component` are used as `(@Card title:{t} count:3 (p = "body"))`. The props are the fields of the
+/// component, written `name:{expr}` or `name:literal`, or just `name` for a variable of the same name, and they are
+/// passed as is: the macro builds the struct literal `Card { title: t, count: 3 }`, so a missing, unknown or mistyped
+/// prop is a compile error. Ending the props with `..` fills the remaining fields from `Default`. The nodes nested in
+/// the component, which can be preceded by `=`, are passed to `Component::render` as its children.
+///
+/// ```
+/// use hteaml::{hteaml, Component, Html, Render};
+///
+/// #[derive(Default)]
+/// struct Button<'a> {
+///     label: &'a str,
+///     primary: bool,
+/// }
+///
+/// impl<'a> Component<'a> for Button<'a> {
+///     fn render(self, children: Html<'a>) -> Html<'a> {
+///         hteaml!((button class:["btn", "btn-primary" => self.primary] = {self.label} {children}))
+///     }
+/// }
+///
+/// let html = hteaml!((p (@Button label:"Go" primary:true) (@Button label:"Back" ..)));
+/// assert_eq!(
+///     html.render(),
+///     Ok(r#"<p><button class="btn btn-primary">Go</button><button class="btn">Back</button></p>"#.into())
+/// );
+/// ```
+///
+/// ```compile_fail
+/// # use hteaml::{hteaml, Component, Html};
+/// struct Card<'a> {
+///     title: &'a str,
+/// }
+/// impl<'a> Component<'a> for Card<'a> {
+///     fn render(self, children: Html<'a>) -> Html<'a> {
+///         hteaml!((div (h2 = {self.title}) {children}))
+///     }
+/// }
+/// let html = hteaml!((@Card (p = "the title prop is missing")));
+/// ```
+///
+/// #### Slots
+/// Besides its children, a component can take content for several insertion points as fields: `(:name ...)` nested in
+/// the component fills the field `name` with the nodes written in it. Slot fields are `Html` or, for optional slots,
+/// `Option<Html>`. Slots left out are filled from `Default` when the props end with `..`, so a component can define
+/// the default content of its slots in its `Default` implementation, or fall back to it when an `Option` is `None`.
+///
+/// ```
+/// use hteaml::{hteaml, Component, Html, Render};
+///
+/// #[derive(Default)]
+/// struct Layout<'a> {
+///     header: Html<'a>,
+///     footer: Option<Html<'a>>,
+/// }
+///
+/// impl<'a> Component<'a> for Layout<'a> {
+///     fn render(self, children: Html<'a>) -> Html<'a> {
+///         let footer = self.footer.unwrap_or_else(|| hteaml!("(c) 2024"));
+///         hteaml!((body (header = {self.header}) (main = {children}) (footer = {footer})))
+///     }
+/// }
+///
+/// let html = hteaml!((@Layout .. (:header (h1 = "Title")) (p = "text")));
+/// assert_eq!(
+///     html.render(),
+///     Ok("<body><header><h1>Title</h1></header><main><p>text</p></main><footer>(c) 2024</footer></body>".into())
+/// );
+/// ```
+///
+/// A component with required props can keep its slots in a field whose type implements `Default` instead. Ending the
+/// props with `..name` fills the field `name` from `Default` and then sets the slots written in the component on it, so
+/// the other slots keep their default content.
+///
+/// ```
+/// use hteaml::{hteaml, Component, Html, Render};
+///
+/// struct Page<'a> {
+///     title: &'a str,
+///     slots: PageSlots<'a>,
+/// }
+///
+/// #[derive(Default)]
+/// struct PageSlots<'a> {
+///     nav: Option<Html<'a>>,
+///     footer: Html<'a>,
+/// }
+///
+/// impl<'a> Component<'a> for Page<'a> {
+///     fn render(self, children: Html<'a>) -> Html<'a> {
+///         let PageSlots { nav, footer } = self.slots;
+///         hteaml!((body (h1 = {self.title}) {nav} (main = {children}) (footer = {footer})))
+///     }
+/// }
+///
+/// let html = hteaml!((@Page title:"Home" ..slots (:footer "(c) 2024") (p = "text")));
+/// assert_eq!(
+///     html.render(),
+///     Ok("<body><h1>Home</h1><main><p>text</p></main><footer>(c) 2024</footer></body>".into())
+/// );
+/// ```
+///
+/// ### Doctype
+/// `(!doctype html)` renders to `<!DOCTYPE html>`. Legacy identifiers can be given as
+/// `(!doctype html public:"-//W3C//DTD XHTML 1.0 Strict//EN" system:"http://www.w3.org/TR/xhtml1/DTD/xhtml1-strict.dtd")`.
+///
+/// ### Comments
+/// `(!-- "text")` or `(!-- {expr})` renders to `<!-- text -->`, both at the top level and as the content of a tag.
+///
+/// ### Escaping
+/// Strings and expressions used as content or attribute values are HTML escaped when rendered.
+/// Trusted markup that must be written as is can be wrapped in `(@raw "<b>markup</b>")` or `(@raw {expr})`,
+/// which can be used both at the top level and as the content of a tag.
 #[proc_macro]
 pub fn hteaml(stream: TokenStream) -> TokenStream {
     let html = parse_macro_input!(stream as Html);
@@ -38,70 +249,348 @@ pub fn hteaml(stream: TokenStream) -> TokenStream {
     .into()
 }
 
-enum Html {
+/// The top level of the macro, a sequence of nodes that becomes a single `Html` value
+struct Html(Vec<Node>);
+
+impl Parse for Html {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let mut nodes = vec![Node::parse(input)?];
+        while !input.is_empty() {
+            nodes.push(Node::parse(input)?);
+        }
+        Ok(Self(nodes))
+    }
+}
+
+impl ToTokens for Html {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        if let [node] = self.0.as_slice() {
+            if let Some(value) = node.html_value() {
+                return quote!(::hteaml::Html::from(#value)).to_tokens(tokens);
+            }
+        }
+        let seq = Sink::Html(proc_macro2::Ident::new(
+            "html",
+            proc_macro2::Span::mixed_site(),
+        ));
+        let nodes = self.0.iter().map(|node| seq.push(node));
+        quote! {
+            let mut #seq = ::std::vec::Vec::<::hteaml::Html>::new();
+            #(#nodes)*
+            ::hteaml::Html::Html(#seq)
+        }
+        .to_tokens(tokens)
+    }
+}
+
+/// A single item of the top level or of the content of a tag
+enum Node {
     Tag(Tag),
+    Text(syn::LitStr),
+    Doctype(Doctype),
+    Comment(Comment),
+    Raw(Raw),
+    Component(component::Component),
     Expr(BracedExpr),
-    Seq(Vec<Html>),
+    If(control::If),
+    For(control::For),
+    Match(control::Match),
+    Let(control::Let),
 }
 
-impl Parse for Html {
+impl Parse for Node {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
-        let element = input
-            .parse()
-            .map(Html::Tag)
-            .or_else(|_| input.parse().map(Html::Expr))?;
-        let mut seq: Vec<_> = vec![];
-        if input.peek(syn::token::Paren) || input.peek(syn::token::Brace) {
-            seq.push(element);
-            while !input.is_empty() {
-                let html = input.parse::<Html>()?;
-                seq.push(html);
+        if input.peek(Token![@]) {
+            return if input.peek2(Token![if]) {
+                input.parse().map(Node::If)
+            } else if input.peek2(Token![for]) {
+                input.parse().map(Node::For)
+            } else if input.peek2(Token![match]) {
+                input.parse().map(Node::Match)
+            } else if input.peek2(Token![let]) {
+                input.parse().map(Node::Let)
+            } else {
+                Err(input.error("expected one of @if, @for, @match or @let"))
+            };
+        }
+        if Raw::peek(input) {
+            input.parse().map(Node::Raw)
+        } else if component::Component::peek(input) {
+            input.parse().map(Node::Component)
+        } else if Doctype::peek(input) {
+            input.parse().map(Node::Doctype)
+        } else if Comment::peek(input) {
+            input.parse().map(Node::Comment)
+        } else if input.peek(syn::token::Paren) {
+            input.parse().map(Node::Tag)
+        } else if input.peek(syn::token::Brace) {
+            input.parse().map(Node::Expr)
+        } else if input.peek(syn::LitStr) {
+            input.parse().map(Node::Text)
+        } else {
+            Err(input.error(
+                "expected a (tag), a string literal, a { braced Rust expression } or control flow such as @if",
+            ))
+        }
+    }
+}
+
+impl Node {
+    /// The expression this node evaluates to when converted with `Html::from`, `None` for control flow
+    fn html_value(&self) -> Option<proc_macro2::TokenStream> {
+        Some(match self {
+            Node::Tag(t) => t.to_token_stream(),
+            Node::Text(s) => s.to_token_stream(),
+            Node::Doctype(d) => d.to_token_stream(),
+            Node::Comment(c) => c.to_token_stream(),
+            Node::Raw(r) => r.to_token_stream(),
+            Node::Component(c) => c.to_token_stream(),
+            Node::Expr(e) => e.to_token_stream(),
+            Node::If(_) | Node::For(_) | Node::Match(_) | Node::Let(_) => return None,
+        })
+    }
+}
+
+/// Where the generated code puts the nodes: the `Vec<Html>` of the top level, or the tag whose content they are
+enum Sink {
+    Html(proc_macro2::Ident),
+    Tag(proc_macro2::Ident),
+}
+
+impl Sink {
+    /// The statement adding `node` to the sink
+    fn push(&self, node: &Node) -> proc_macro2::TokenStream {
+        match (node, self) {
+            (Node::If(n), _) => n.expand(self),
+            (Node::For(n), _) => n.expand(self),
+            (Node::Match(n), _) => n.expand(self),
+            (Node::Let(n), _) => n.to_token_stream(),
+            (Node::Expr(e), Sink::Tag(tag)) => quote! {
+                #tag.push_child({
+                    use ::hteaml::__private::{IntoContent as _, IterContent as _};
+                    ::hteaml::__private::Wrap(::core::option::Option::Some(#e)).into_content()
+                });
+            },
+            (node, Sink::Tag(tag)) => {
+                let value = node.html_value();
+                quote!(#tag.push_child(#value);)
+            }
+            (node, Sink::Html(seq)) => {
+                let value = node.html_value();
+                quote!(#seq.push(::hteaml::Html::from(#value));)
             }
-            return Ok(Self::Seq(seq));
         }
-        Ok(element)
     }
 }
 
-impl ToTokens for Html {
+impl ToTokens for Sink {
     fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
         match self {
-            Html::Expr(e) => quote! {
-                ::hteaml::Html::from(#e)
-            },
-            Html::Tag(t) => quote! {
-                ::hteaml::Html::Tag(#t)
-            },
-            Html::Seq(s) => {
-                let tag = s.iter();
-                quote! {
-                    ::hteaml::Html::Html(vec![
-                       #(#tag.into()),*
-                    ])
+            Sink::Html(ident) | Sink::Tag(ident) => ident.to_tokens(tokens),
+        }
+    }
+}
+
+/// Trusted markup written as `(@raw "markup")` or `(@raw {expr})`
+struct Raw(Value);
+
+impl Raw {
+    /// Checks whether the next token tree is a parenthesized group starting with `@raw`
+    fn peek(input: syn::parse::ParseStream) -> bool {
+        input
+            .cursor()
+            .group(proc_macro2::Delimiter::Parenthesis)
+            .and_then(|(inner, _, _)| inner.punct())
+            .filter(|(punct, _)| punct.as_char() == '@')
+            .and_then(|(_, rest)| rest.ident())
+            .is_some_and(|(ident, _)| ident == "raw")
+    }
+}
+
+impl Parse for Raw {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let content;
+        syn::parenthesized!(content in input);
+        content.parse::<Token![@]>()?;
+        content.parse::<syn::Ident>()?;
+        let val = match content.parse::<Value>()? {
+            Value::Ident(id) => {
+                return Err(syn::Error::new(
+                    id.span(),
+                    "expected a string literal or a { braced Rust expression } as raw markup",
+                ))
+            }
+            val => val,
+        };
+        if !content.is_empty() {
+            return Err(content.error("expected a single value inside (@raw ...)"));
+        }
+        Ok(Self(val))
+    }
+}
+
+impl ToTokens for Raw {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        let val = &self.0;
+        quote!(::hteaml::Raw::new(#val)).to_tokens(tokens)
+    }
+}
+
+/// A document type declaration written as `(!doctype html)`, optionally followed by `public:val` and `system:val`
+///
+/// The quoted form `("!DOCTYPE" html)` is accepted too, it was the way to write a doctype before the node existed.
+struct Doctype {
+    name: Value,
+    public_id: Option<Value>,
+    system_id: Option<Value>,
+}
+
+impl Doctype {
+    /// Checks whether the next token tree is a parenthesized group starting with `!doctype` or `"!doctype"`
+    fn peek(input: syn::parse::ParseStream) -> bool {
+        let Some((inner, _, _)) = input.cursor().group(proc_macro2::Delimiter::Parenthesis) else {
+            return false;
+        };
+        let bang = inner
+            .punct()
+            .filter(|(punct, _)| punct.as_char() == '!')
+            .and_then(|(_, rest)| rest.ident())
+            .is_some_and(|(ident, _)| ident.to_string().eq_ignore_ascii_case("doctype"));
+        bang || inner
+            .literal()
+            .is_some_and(|(lit, _)| Self::is_quoted(&lit))
+    }
+
+    /// Whether `lit` is the string literal `"!DOCTYPE"`, in any case
+    fn is_quoted(lit: &proc_macro2::Literal) -> bool {
+        syn::parse2::<syn::LitStr>(lit.to_token_stream())
+            .is_ok_and(|lit| lit.value().eq_ignore_ascii_case("!doctype"))
+    }
+}
+
+impl Parse for Doctype {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let content;
+        syn::parenthesized!(content in input);
+        if content.peek(syn::LitStr) {
+            content.parse::<syn::LitStr>()?;
+        } else {
+            content.parse::<Token![!]>()?;
+            content.parse::<syn::Ident>()?;
+        }
+        let mut doctype = Self {
+            name: content.parse()?,
+            public_id: None,
+            system_id: None,
+        };
+        while !content.is_empty() {
+            let key = content.parse::<syn::Ident>()?;
+            content.parse::<Token![:]>()?;
+            let val = Some(content.parse()?);
+            match key.to_string().as_str() {
+                "public" => doctype.public_id = val,
+                "system" => doctype.system_id = val,
+                _ => {
+                    return Err(syn::Error::new(
+                        key.span(),
+                        "expected `public` or `system` identifiers in (!doctype ...)",
+                    ))
                 }
             }
         }
+        Ok(doctype)
+    }
+}
+
+impl ToTokens for Doctype {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        let name = &self.name;
+        let public_id = self.public_id.iter();
+        let system_id = self.system_id.iter();
+        quote! {
+            ::hteaml::Doctype::new(#name)
+            #(.public_id(#public_id))*
+            #(.system_id(#system_id))*
+        }
         .to_tokens(tokens)
     }
 }
 
+/// A comment written as `(!-- "text")` or `(!-- {expr})`
+struct Comment(Value);
+
+impl Comment {
+    /// Checks whether the next token tree is a parenthesized group starting with `!--`
+    fn peek(input: syn::parse::ParseStream) -> bool {
+        input
+            .cursor()
+            .group(proc_macro2::Delimiter::Parenthesis)
+            .and_then(|(inner, _, _)| inner.punct())
+            .filter(|(punct, _)| punct.as_char() == '!')
+            .and_then(|(_, rest)| rest.punct())
+            .filter(|(punct, _)| punct.as_char() == '-')
+            .and_then(|(_, rest)| rest.punct())
+            .is_some_and(|(punct, _)| punct.as_char() == '-')
+    }
+}
+
+impl Parse for Comment {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let content;
+        syn::parenthesized!(content in input);
+        content.parse::<Token![!]>()?;
+        content.parse::<Token![-]>()?;
+        content.parse::<Token![-]>()?;
+        let val =
+            match content.parse::<Value>()? {
+                Value::Ident(id) => return Err(syn::Error::new(
+                    id.span(),
+                    "expected a string literal or a { braced Rust expression } as the comment text",
+                )),
+                val => val,
+            };
+        if !content.is_empty() {
+            return Err(content.error("expected a single value inside (!-- ...)"));
+        }
+        Ok(Self(val))
+    }
+}
+
+impl ToTokens for Comment {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        let val = &self.0;
+        quote!(::hteaml::Comment::new(#val)).to_tokens(tokens)
+    }
+}
+
 struct Tag {
     name: Value,
-    attrs: Vec<Attr>,
-    cont: Content,
+    attrs: Vec<AttrItem>,
+    cont: Vec<Node>,
 }
 
 impl ToTokens for Tag {
     fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
         let name = &self.name;
         let attrs = self.attrs.iter();
-        let content = &self.cont;
         let tag = quote! {
             ::hteaml::Tag::new(#name)
             #(#attrs)*
-            #content
         };
-        tag.to_tokens(tokens);
+        if self.cont.is_empty() {
+            return tag.to_tokens(tokens);
+        }
+        let sink = Sink::Tag(proc_macro2::Ident::new(
+            "tag",
+            proc_macro2::Span::mixed_site(),
+        ));
+        let content = self.cont.iter().map(|node| sink.push(node));
+        quote!({
+            let mut #sink = #tag;
+            #(#content)*
+            #sink
+        })
+        .to_tokens(tokens)
     }
 }
 
@@ -109,55 +598,419 @@ impl Parse for Tag {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
         let content;
         syn::parenthesized!(content in input);
-        let name = content.parse()?;
-        let mut attrs = vec![];
-        let fork = content.fork();
-        while let Ok(attr) = fork.parse() {
+        let name: Value = content.parse()?;
+        let mut attrs = Tag::parse_shorthand(&content)?
+            .into_iter()
+            .map(AttrItem::Attr)
+            .collect::<Vec<_>>();
+        loop {
+            let fork = content.fork();
+            let Ok(attr) = fork.parse() else { break };
             attrs.push(attr);
-        }
-        if !attrs.is_empty() {
             content.advance_to(&fork);
         }
-        let cont = match content.peek(Token![=]) {
-            true => {
-                content.parse::<Token![=]>()?;
-                content.parse()?
+        if content.peek(Token![=]) {
+            content.parse::<Token![=]>()?;
+            if content.is_empty() {
+                return Err(content.error("expected content after `=`"));
             }
-            false => content.parse::<Content>().ok().unwrap_or(Content::None),
-        };
+        }
+        let mut cont = vec![];
+        while !content.is_empty() {
+            cont.push(content.parse()?);
+        }
+        if let (Some(tag), false) = (name.literal(), cont.is_empty()) {
+            if is_void_element(&tag) {
+                return Err(syn::Error::new(
+                    name.span(),
+                    format!("void element `{tag}` cannot have content"),
+                ));
+            }
+        }
         Ok(Self { name, attrs, cont })
     }
 }
 
+impl Tag {
+    /// Parse the `.class` and `#id` shorthands written right after the tag name into `class` and `id` attributes
+    fn parse_shorthand(input: syn::parse::ParseStream) -> syn::Result<Vec<Attr>> {
+        let span = input.span();
+        let mut classes = vec![];
+        let mut id = None;
+        loop {
+            if input.peek(Token![.]) && !input.peek(Token![..]) {
+                input.parse::<Token![.]>()?;
+                classes.push(parse_hyphenated(input)?);
+            } else if input.peek(Token![#]) {
+                let hash = input.parse::<Token![#]>()?;
+                if id.replace(parse_hyphenated(input)?).is_some() {
+                    return Err(syn::Error::new(hash.span, "a tag can only have one #id"));
+                }
+            } else {
+                break;
+            }
+        }
+        let attr = |key: &str, val: &str| Attr {
+            key: Value::Str(syn::LitStr::new(key, span)),
+            val: AttrVal::Value(Value::Str(syn::LitStr::new(val, span))),
+        };
+        let classes = (!classes.is_empty()).then(|| attr("class", &classes.join(" ")));
+        Ok(classes
+            .into_iter()
+            .chain(id.map(|id| attr("id", &id)))
+            .collect())
+    }
+}
+
+/// Parse a name made of identifiers, Rust keywords included, joined by `-`, such as `btn-primary`
+///
+/// The parts after a `-` can also be integers, written as they are in the source: `mt-2`, `col-md-6`.
+fn parse_hyphenated(input: syn::parse::ParseStream) -> syn::Result<String> {
+    use syn::ext::IdentExt;
+
+    let mut name = syn::Ident::parse_any(input)?.to_string();
+    while input.peek(Token![-]) {
+        input.parse::<Token![-]>()?;
+        name.push('-');
+        match input.peek(syn::LitInt) {
+            true => name.push_str(&input.parse::<syn::LitInt>()?.to_string()),
+            false => name.push_str(&syn::Ident::parse_any(input)?.to_string()),
+        }
+    }
+    Ok(name)
+}
+
+/// Elements that cannot have any content, kept in sync with the list used for rendering in `hteaml`
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source",
+    "track", "wbr",
+];
+
+fn is_void_element(name: &str) -> bool {
+    VOID_ELEMENTS.iter().any(|e| e.eq_ignore_ascii_case(name))
+}
+
+/// An entry of the attribute list of a tag
+enum AttrItem {
+    Attr(Attr),
+    /// `..{attrs}`, an iterator of key and value pairs
+    Spread(BracedExpr),
+}
+
+impl Parse for AttrItem {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        if !input.peek(Token![..]) {
+            return input.parse().map(AttrItem::Attr);
+        }
+        input.parse::<Token![..]>()?;
+        match input.peek(syn::token::Brace) {
+            true => input.parse().map(AttrItem::Spread),
+            false => Err(input.error("expected a { braced Rust expression }, as in `..{attrs}`")),
+        }
+    }
+}
+
+impl ToTokens for AttrItem {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        match self {
+            AttrItem::Attr(attr) => attr.to_tokens(tokens),
+            AttrItem::Spread(attrs) => quote!(.attrs(#attrs)).to_tokens(tokens),
+        }
+    }
+}
+
 #[derive(Clone)]
 struct Attr {
     key: Value,
-    val: Option<Value>,
+    val: AttrVal,
+}
+
+/// What follows the key of an attribute
+#[derive(Clone)]
+enum AttrVal {
+    /// `key`, an attribute without a value
+    None,
+    /// `key:val`
+    Value(Value),
+    /// `key:("val" {expr} ...)`, values written one after another
+    Concat(Vec<Value>),
+    /// `key?:{cond}`, an attribute without a value that is only added when `cond` is true
+    Toggle(BracedExpr),
+    /// `key:[class, class => cond, ...]`, a class list
+    List(Vec<ListItem>),
+    /// `key:{ name: value, ... }`, the declarations of a style
+    Style(Vec<(syn::LitStr, AttrVal)>),
+}
+
+/// An item of a class list, `class` or `class => cond`
+#[derive(Clone)]
+struct ListItem {
+    class: Value,
+    cond: Option<syn::Expr>,
+}
+
+impl Parse for ListItem {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let class = match input.peek(syn::LitStr) || input.peek(syn::token::Brace) {
+            true => input.parse()?,
+            false => {
+                let span = input.span();
+                Value::Str(syn::LitStr::new(&parse_hyphenated(input)?, span))
+            }
+        };
+        let cond = match input.peek(Token![=>]) {
+            true => {
+                input.parse::<Token![=>]>()?;
+                Some(input.parse()?)
+            }
+            false => None,
+        };
+        Ok(Self { class, cond })
+    }
+}
+
+impl ToTokens for ListItem {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        let class = &self.class;
+        match &self.cond {
+            Some(cond) => quote!(.class_if(#class, #cond)),
+            None => quote!(.class(#class)),
+        }
+        .to_tokens(tokens)
+    }
 }
 
 impl ToTokens for Attr {
     fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
         let key = &self.key;
-        let empty_val = Value::Str(syn::LitStr::new("", tokens.span()));
-        let val = self.val.as_ref().unwrap_or(&empty_val);
-        quote!(.attr(#key, #val)).to_tokens(tokens);
+        match &self.val {
+            AttrVal::Toggle(cond) => quote!(.bool_attr(#key, #cond)),
+            val => quote!(.attr(#key, #val)),
+        }
+        .to_tokens(tokens);
     }
 }
 
+/// The value given to `Tag::attr`, or the condition given to `Tag::bool_attr` for `key?:{cond}`
+impl ToTokens for AttrVal {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        match self {
+            AttrVal::None => quote!(""),
+            AttrVal::Value(val) => val.to_token_stream(),
+            AttrVal::Concat(parts) => quote! {
+                ::hteaml::AttrValue::Concat(::std::vec![
+                    #(::hteaml::AttrValue::from(#parts)),*
+                ])
+            },
+            AttrVal::Toggle(cond) => cond.to_token_stream(),
+            AttrVal::List(items) => quote!(::hteaml::ClassList::new() #(#items)*),
+            AttrVal::Style(decls) => {
+                let (names, values): (Vec<_>, Vec<_>) = decls.iter().cloned().unzip();
+                quote!(::hteaml::Style::new() #(.prop(#names, #values))*)
+            }
+        }
+        .to_tokens(tokens)
+    }
+}
+
+/// Checks whether the next token tree is a `{ name: ... }` style map rather than a braced Rust expression
+///
+/// No Rust expression starts with a name followed by a single `:`, so the two can't be confused.
+fn peek_style(input: syn::parse::ParseStream) -> bool {
+    let Some((mut cursor, _, _)) = input.cursor().group(proc_macro2::Delimiter::Brace) else {
+        return false;
+    };
+    loop {
+        let Some((_, rest)) = cursor.ident() else {
+            return false;
+        };
+        match rest.punct() {
+            Some((punct, next)) if punct.as_char() == '-' => cursor = next,
+            Some((punct, next)) if punct.as_char() == ':' => {
+                return punct.spacing() == proc_macro2::Spacing::Alone
+                    || next.punct().is_none_or(|(p, _)| p.as_char() != ':');
+            }
+            _ => return false,
+        }
+    }
+}
+
+/// Parse the declarations of a `{ name: value, ... }` style map
+fn parse_style(input: syn::parse::ParseStream) -> syn::Result<AttrVal> {
+    let content;
+    syn::braced!(content in input);
+    let mut decls = vec![];
+    while !content.is_empty() {
+        let span = content.span();
+        let name = syn::LitStr::new(&parse_hyphenated(&content)?, span);
+        content.parse::<Token![:]>()?;
+        let span = content.span();
+        let first = if content.peek(syn::LitStr) || content.peek(syn::token::Brace) {
+            content.parse()?
+        } else if content.peek(syn::Lit) {
+            match content.parse()? {
+                syn::Lit::Int(lit) => Value::Str(syn::LitStr::new(&lit.to_string(), span)),
+                syn::Lit::Float(lit) => Value::Str(syn::LitStr::new(&lit.to_string(), span)),
+                _ => return Err(syn::Error::new(span, "expected a CSS value")),
+            }
+        } else {
+            Value::Str(syn::LitStr::new(&parse_hyphenated(&content)?, span))
+        };
+        decls.push((name, Attr::parse_concat(first, &content)?));
+        if !content.is_empty() {
+            content.parse::<Token![,]>()?;
+        }
+    }
+    Ok(AttrVal::Style(decls))
+}
+
 impl Parse for Attr {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
-        let key = input.parse();
-        let mut val = None;
-        if input.parse::<Token![:]>().is_ok() {
-            val = input.parse::<Value>().ok();
-            if key.is_err() || val.is_none() {
+        if input.peek(syn::LitStr) || input.peek(syn::token::Brace) {
+            let key = input.parse()?;
+            let val = if let Some(cond) = Attr::parse_toggle(input)? {
+                AttrVal::Toggle(cond)
+            } else if input.peek(Token![:]) {
+                input.parse::<Token![:]>()?;
+                Attr::parse_value(input)
+                    .map_err(|_| input.error("expected key:value pairs for attributes"))?
+            } else {
+                AttrVal::None
+            };
+            return Ok(Self { key, val });
+        }
+        Attr::parse_name(input)
+    }
+}
+
+impl Attr {
+    /// Parse an unquoted attribute, a run of names joined by `-` and `:`, with an optional leading `@` or `:`
+    ///
+    /// The last `:` of the run separates the key from the value, which is either the rest of the run or a string
+    /// literal or `{expr}` following it. Everything before it, including other `:`, is the key:
+    /// `x-on:submit:{handler}` is the key `x-on:submit`, while `xlink:href` alone is the key `xlink` with the value `href`.
+    fn parse_name(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        use syn::ext::IdentExt;
+
+        let span = input.span();
+        let key = |name: &str| Value::Str(syn::LitStr::new(name, span));
+        let mut name = String::new();
+        if input.peek(Token![@]) {
+            if input.peek2(Token![if])
+                || input.peek2(Token![for])
+                || input.peek2(Token![match])
+                || input.peek2(Token![let])
+            {
+                return Err(input.error("control flow is not an attribute"));
+            }
+            input.parse::<Token![@]>()?;
+            name.push('@');
+        } else if input.peek(Token![:]) {
+            input.parse::<Token![:]>()?;
+            name.push(':');
+        }
+        let mut separator = None;
+        loop {
+            if separator.is_some_and(|i| i + 1 == name.len())
+                && (input.peek(syn::LitStr)
+                    || input.peek(syn::token::Brace)
+                    || input.peek(syn::token::Bracket)
+                    || input.peek(syn::token::Paren))
+            {
+                name.pop();
+                return Ok(Self {
+                    key: key(&name),
+                    val: Attr::parse_value(input)?,
+                });
+            }
+            name.push_str(&syn::Ident::parse_any(input)?.to_string());
+            if let Some(cond) = Attr::parse_toggle(input)? {
+                return Ok(Self {
+                    key: key(&name),
+                    val: AttrVal::Toggle(cond),
+                });
+            } else if input.peek(Token![-]) {
+                input.parse::<Token![-]>()?;
+                name.push('-');
+            } else if input.peek(Token![:]) {
+                input.parse::<Token![:]>()?;
+                separator = Some(name.len());
+                name.push(':');
+            } else {
+                break;
+            }
+        }
+        Ok(match separator {
+            Some(i) => Self {
+                key: key(&name[..i]),
+                val: AttrVal::Value(key(&name[i + 1..])),
+            },
+            None => Self {
+                key: key(&name),
+                val: AttrVal::None,
+            },
+        })
+    }
+
+    /// Parse the value after the `:` separator, either a `[class list]`, a `{ style: map }`, a `(concatenation)` or a
+    /// single value
+    fn parse_value(input: syn::parse::ParseStream) -> syn::Result<AttrVal> {
+        if peek_style(input) {
+            return parse_style(input);
+        }
+        if input.peek(syn::token::Paren) {
+            let content;
+            let parens = syn::parenthesized!(content in input);
+            if content.is_empty() {
                 return Err(syn::Error::new(
-                    input.span(),
-                    "expected key:value pairs for attributes",
+                    parens.span.join(),
+                    "expected values to concatenate, as in `key:(\"a\" {b})`",
                 ));
             }
+            let first = content.parse()?;
+            let val = Attr::parse_concat(first, &content)?;
+            if !content.is_empty() {
+                return Err(
+                    content.error("expected a string literal or a { braced Rust expression }")
+                );
+            }
+            return Ok(val);
+        }
+        if !input.peek(syn::token::Bracket) {
+            return input.parse().map(AttrVal::Value);
+        }
+        let content;
+        syn::bracketed!(content in input);
+        let items = content.parse_terminated(ListItem::parse, Token![,])?;
+        Ok(AttrVal::List(items.into_iter().collect()))
+    }
+
+    /// Parse the string literals and `{expr}`s following the value `first`, which are concatenated to it
+    fn parse_concat(first: Value, input: syn::parse::ParseStream) -> syn::Result<AttrVal> {
+        let mut parts = vec![];
+        while input.peek(syn::LitStr) || input.peek(syn::token::Brace) {
+            parts.push(input.parse()?);
+        }
+        Ok(match parts.is_empty() {
+            true => AttrVal::Value(first),
+            false => {
+                parts.insert(0, first);
+                AttrVal::Concat(parts)
+            }
+        })
+    }
+
+    /// Parse the `?:{cond}` of a boolean attribute, if it is next
+    fn parse_toggle(input: syn::parse::ParseStream) -> syn::Result<Option<BracedExpr>> {
+        if !(input.peek(Token![?]) && input.peek2(Token![:])) {
+            return Ok(None);
+        }
+        input.parse::<Token![?]>()?;
+        input.parse::<Token![:]>()?;
+        match input.peek(syn::token::Brace) {
+            true => input.parse().map(Some),
+            false => Err(input.error("expected a { braced bool expression }, as in `key?:{cond}`")),
         }
-        Ok(Self { key: key?, val })
     }
 }
 
@@ -170,7 +1023,9 @@ enum Value {
 
 impl Parse for Value {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
-        match input.parse::<syn::Ident>() {
+        use syn::ext::IdentExt;
+
+        match syn::Ident::parse_any(input) {
             Ok(val) => Ok(Self::Ident(val)),
             Err(_) => match input.parse::<syn::LitStr>() {
                 Ok(s) => Ok(Self::Str(s)),
@@ -188,76 +1043,24 @@ impl Parse for Value {
     }
 }
 
-impl ToTokens for Value {
-    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+impl Value {
+    /// The value if it is known at compile time
+    fn literal(&self) -> Option<String> {
         match self {
-            Value::Expr(e) => e.to_tokens(tokens),
-            Value::Ident(id) => quote!(stringify!(#id)).to_tokens(tokens),
-            Value::Str(s) => s.to_tokens(tokens),
-        }
-    }
-}
-
-enum Content {
-    Str(syn::LitStr),
-    Expr(BracedExpr),
-    Html(Box<Html>),
-    Seq(Vec<Content>),
-    None,
-}
-
-impl Parse for Content {
-    // fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
-    //     match input.parse::<syn::LitStr>() {
-    //         Ok(s) => Ok(Self::Str(s)),
-    //         Err(_) => match input.parse::<Html>() {
-    //             Ok(h) => Ok(Self::Html(Box::new(h))),
-    //             Err(_) => match input.parse::<BracedExpr>() {
-    //                 Ok(e) => Ok(Self::Expr(e)),
-    //                 Err(_) => Err(syn::Error::new(
-    //                     input.span(),
-    //                     "expected either a string literal or a nested tag or a { Rust expression }",
-    //                 )),
-    //             },
-    //         },
-    //     }
-    // }
-
-    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
-        let content = input
-            .parse()
-            .map(Content::Str)
-            .or_else(|_| input.parse().map(Content::Expr))
-            .or_else(|_| input.parse().map(|h: Html| Content::Html(Box::new(h))))
-            .map_err(|_| {
-                syn::Error::new(
-                    input.span(),
-                    "expected a string literal, a Rust expression or atag",
-                )
-            })?;
-        let mut seq = vec![];
-        if input.peek(syn::token::Paren) || input.peek(syn::token::Brace) || input.peek(syn::LitStr)
-        {
-            seq.push(content);
-            while !input.is_empty() {
-                seq.push(input.parse()?);
-            }
-            return Ok(Self::Seq(seq));
+            Value::Expr(_) => None,
+            Value::Ident(id) => Some(id.to_string()),
+            Value::Str(s) => Some(s.value()),
         }
-        Ok(content)
     }
 }
 
-impl ToTokens for Content {
+impl ToTokens for Value {
     fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
         match self {
-            Content::Str(s) => quote!(.content(#s)),
-            Content::Html(h) => quote!(.content(#h)),
-            Content::Expr(e) => quote!(.content(#e)),
-            Content::None => quote!(.self_closing()),
-            Content::Seq(s) => return s.iter().for_each(|e| e.to_tokens(tokens)),
+            Value::Expr(e) => e.to_tokens(tokens),
+            Value::Ident(id) => quote!(stringify!(#id)).to_tokens(tokens),
+            Value::Str(s) => s.to_tokens(tokens),
         }
-        .to_tokens(tokens);
     }
 }
 
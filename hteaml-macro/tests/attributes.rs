@@ -0,0 +1,246 @@
+//! Tests for the attribute syntax of the `hteaml` macro
+use hteaml::Render;
+use hteaml_macro::hteaml;
+
+#[test]
+fn keyword_keys() {
+    let html = hteaml!((label for:name (input type:text name:name)));
+    assert_eq!(
+        html.render(),
+        Ok(r#"<label for="name"><input type="text" name="name"></label>"#.into())
+    );
+}
+
+#[test]
+fn hyphenated_keys() {
+    let id = 7;
+    let html = hteaml!((button data-id:{id} aria-label:"Close" hx-get:"/items" hx-swap:outer-html));
+    assert_eq!(
+        html.render(),
+        Ok(
+            r#"<button data-id="7" aria-label="Close" hx-get="/items" hx-swap="outer-html"></button>"#
+                .into()
+        )
+    );
+}
+
+#[test]
+fn framework_keys() {
+    let html = hteaml!((button @click:"open = !open" :class:{"active"} x-on:submit:"save()"));
+    assert_eq!(
+        html.render(),
+        Ok(
+            r#"<button @click="open = !open" :class="active" x-on:submit="save()"></button>"#
+                .into()
+        )
+    );
+}
+
+#[test]
+fn namespaced_keys() {
+    let html = hteaml!((svg (use xlink:href:"#icon") (text xml:lang:en = "hi")));
+    assert_eq!(
+        html.render(),
+        Ok(r##"<svg><use xlink:href="#icon"></use><text xml:lang="en">hi</text></svg>"##.into())
+    );
+}
+
+#[test]
+fn last_colon_separates_value() {
+    let html = hteaml!((a href:home x-bind:class));
+    assert_eq!(
+        html.render(),
+        Ok(r#"<a href="home" x-bind="class"></a>"#.into())
+    );
+}
+
+#[test]
+fn keys_without_values() {
+    let html = hteaml!((input type:checkbox checked data-x @focus "other-key"));
+    assert_eq!(
+        html.render(),
+        Ok(r#"<input type="checkbox" checked data-x @focus other-key>"#.into())
+    );
+}
+
+#[test]
+fn quoted_keys() {
+    let html = hteaml!((form "x-on:submit.prevent":"save()"));
+    assert_eq!(
+        html.render(),
+        Ok(r#"<form x-on:submit.prevent="save()"></form>"#.into())
+    );
+}
+
+#[test]
+fn attributes_before_control_flow() {
+    let show = true;
+    let html = hteaml!((div @click:"go()" @if {show} (p = "shown")));
+    assert_eq!(
+        html.render(),
+        Ok(r#"<div @click="go()"><p>shown</p></div>"#.into())
+    );
+}
+
+#[test]
+fn optional_values() {
+    let link = Some("/home");
+    let title: Option<String> = None;
+    let html = hteaml!((a href:{link} title:{title} = "home"));
+    assert_eq!(html.render(), Ok(r#"<a href="/home">home</a>"#.into()));
+}
+
+#[test]
+fn boolean_attributes() {
+    let is_locked = true;
+    let maybe_val: Option<u32> = None;
+    let html = hteaml! {
+        (select
+            (option selected?:{!is_locked} = "a")
+            (option "data-on"?:{is_locked} aria-hidden:{is_locked} = "b"))
+        (input disabled?:{is_locked} x-bind:open?:{false} value:{maybe_val})
+        (button disabled:{false} hidden:{is_locked})
+    };
+    assert_eq!(
+        html.render(),
+        Ok(concat!(
+            r#"<select><option>a</option><option data-on aria-hidden="true">b</option></select>"#,
+            r#"<input disabled><button hidden></button>"#
+        )
+        .into())
+    );
+}
+
+#[test]
+fn class_and_id_shorthand() {
+    let html = hteaml!((div.card.is-active #main = (p.text = "hi")));
+    assert_eq!(
+        html.render(),
+        Ok(r#"<div class="card is-active" id="main"><p class="text">hi</p></div>"#.into())
+    );
+}
+
+#[test]
+fn numbered_classes() {
+    let wide = true;
+    let html = hteaml! {
+        (div.mt-2.col-md-6 #step-1 (p.p-4 class:[px-3, col-lg-12 => wide, h-0 => !wide] = "x"))
+    };
+    assert_eq!(
+        html.render(),
+        Ok(
+            r#"<div class="mt-2 col-md-6" id="step-1"><p class="p-4 px-3 col-lg-12">x</p></div>"#
+                .into()
+        )
+    );
+}
+
+#[test]
+fn shorthand_merges_with_class_attribute() {
+    let extra = "wide";
+    let html = hteaml!((section #intro.box class:{extra} data-x:y class:box));
+    assert_eq!(
+        html.render(),
+        Ok(r#"<section class="box wide" id="intro" data-x="y"></section>"#.into())
+    );
+}
+
+#[test]
+fn class_lists() {
+    let is_primary = true;
+    let extra = String::from("wide btn");
+    let html = hteaml! {
+        (button.base class:["btn", btn-primary => is_primary, "small" => !is_primary, {extra}])
+        (p class:["hidden" => false])
+        (span rel:[{"a"}, "b"] = "x")
+    };
+    assert_eq!(
+        html.render(),
+        Ok(concat!(
+            r#"<button class="base btn btn-primary wide"></button>"#,
+            r#"<p></p><span rel="a b">x</span>"#
+        )
+        .into())
+    );
+}
+
+#[test]
+fn spread_attributes() {
+    use std::collections::{BTreeMap, HashMap};
+
+    let extra = BTreeMap::from([("type", "submit"), ("class", "wide"), ("data-id", "7")]);
+    let aria = HashMap::from([("aria-label", "Go")]);
+    let title: Option<(&str, &str)> = None;
+    let html = hteaml! {
+        (button.btn type:button ..{&extra} ..{aria} ..{title} = "Go")
+        (button ..{&extra} type:button ..{[("disabled", None::<&str>)]})
+    };
+    assert_eq!(
+        html.render(),
+        Ok(concat!(
+            r#"<button class="btn wide" type="submit" data-id="7" aria-label="Go">Go</button>"#,
+            r#"<button class="wide" data-id="7" type="button"></button>"#
+        )
+        .into())
+    );
+}
+
+#[test]
+fn spread_hostile_keys() {
+    use std::collections::HashMap;
+
+    let map = HashMap::from([
+        (r#""><script>alert(1)</script>"#, "x"),
+        ("x onmouseover=alert(1) y", "x"),
+    ]);
+    let html = hteaml!((button ..{&map} = "Go"));
+    assert_eq!(html.render(), Ok("<button>Go</button>".into()));
+}
+
+#[test]
+fn concatenated_values() {
+    let id = 42;
+    let kind = "primary";
+    let html = hteaml! {
+        (a href:("/users/" {id} "/edit") class:("btn btn-" {kind}) title:(edit {" \""} {id}) = "Edit")
+    };
+    assert_eq!(
+        html.render(),
+        Ok(
+            r#"<a href="/users/42/edit" class="btn btn-primary" title="edit &quot;42">Edit</a>"#
+                .into()
+        )
+    );
+}
+
+#[test]
+fn values_are_not_concatenated_without_parentheses() {
+    let who = "bob";
+    let html = hteaml!((a href:"/" "home") (a href:"/" = "home") (p class:x {who}));
+    assert_eq!(
+        html.render(),
+        Ok(r#"<a href="/" home></a><a href="/">home</a><p class="x" bob></p>"#.into())
+    );
+}
+
+#[test]
+fn style_maps() {
+    fn px(n: u32) -> String {
+        format!("{n}px")
+    }
+    let top = 4;
+    let color: Option<&str> = None;
+    let html = hteaml! {
+        (p style:{ display: flex, margin-top: {px(top)}, color: {color}, width: {top * 10} "%", line-height: 1.5rem, font-family: r#""A", serif"# })
+        (p style:{ color: {color} })
+        (p title:{ if top > 0 { "x" } else { "y" } })
+    };
+    assert_eq!(
+        html.render(),
+        Ok(concat!(
+            r#"<p style="display: flex; margin-top: 4px; width: 40%; line-height: 1.5rem; font-family: &quot;A&quot;, serif"></p>"#,
+            r#"<p></p><p title="x"></p>"#
+        )
+        .into())
+    );
+}
@@ -1,4 +1,5 @@
-use hteaml::Render;
+//! Tests for the basic syntax of the `hteaml` macro
+use hteaml::{Html, Render};
 use hteaml_macro::hteaml;
 
 #[test]
@@ -53,15 +54,24 @@ fn tag_nested() {
 }
 
 #[test]
-fn self_closing() {
+fn empty_tag() {
     let tag = hteaml!((mytag));
-    assert_eq!(tag.render(), Ok("<mytag>".into()));
+    assert_eq!(tag.render(), Ok("<mytag></mytag>".into()));
 }
 
 #[test]
-fn self_closing_with_attrs() {
+fn empty_tag_with_attrs() {
     let tag = hteaml!((mytag hello:world));
-    assert_eq!(tag.render(), Ok(r#"<mytag hello="world">"#.into()));
+    assert_eq!(tag.render(), Ok(r#"<mytag hello="world"></mytag>"#.into()));
+}
+
+#[test]
+fn void_elements() {
+    let html = hteaml!((p = "a" (br) "b" (img src:"x.png") (input "type":text)));
+    assert_eq!(
+        html.render(),
+        Ok(r#"<p>a<br>b<img src="x.png"><input type="text"></p>"#.into())
+    );
 }
 
 #[test]
@@ -73,6 +83,87 @@ fn multi_tag() {
     assert_eq!(html.render(), Ok("<!DOCTYPE html><p>hello</p>".into()))
 }
 
+#[test]
+fn doctype_identifiers() {
+    let system = "http://www.w3.org/TR/xhtml1/DTD/xhtml1-strict.dtd";
+    let html = hteaml!((!DOCTYPE html public:"-//W3C//DTD XHTML 1.0 Strict//EN" system:{system}));
+    assert_eq!(
+        html.render(),
+        Ok(format!(
+            r#"<!DOCTYPE html PUBLIC "-//W3C//DTD XHTML 1.0 Strict//EN" "{system}">"#
+        ))
+    );
+    assert_eq!(
+        hteaml!((!doctype html) ("!doctype" html)).render(),
+        Ok("<!DOCTYPE html><!DOCTYPE html>".into())
+    );
+    assert_eq!(
+        hteaml!((html (!doctype html))).render(),
+        Ok("<html><!DOCTYPE html></html>".into())
+    );
+}
+
+#[test]
+fn formatted_values() {
+    let count = 3u32;
+    let price = 9.99;
+    let addr = std::net::Ipv4Addr::LOCALHOST;
+    let html = hteaml!((span title:{count} = {price} " " {'x'} " " {hteaml::Fmt::display(&addr)}));
+    assert_eq!(
+        html.render(),
+        Ok(r#"<span title="3">9.99 x 127.0.0.1</span>"#.into())
+    );
+}
+
+#[test]
+fn iterator_content() {
+    let items = ["one", "<two>"];
+    let html = hteaml!((ul = { items.iter().map(|item| hteaml!((li = { *item }))) }));
+    assert_eq!(
+        html.render(),
+        Ok("<ul><li>one</li><li>&lt;two&gt;</li></ul>".into())
+    );
+    let tags = vec![hteaml::Tag::new("br"), hteaml::Tag::new("hr")];
+    assert_eq!(
+        hteaml!((p = {tags} "end")).render(),
+        Ok("<p><br><hr>end</p>".into())
+    );
+}
+
+#[test]
+fn option_content() {
+    let user: Option<&str> = None;
+    let badge = Some(hteaml!((b = "new")));
+    let html = hteaml!((p = {user} {badge} {Some("text")}));
+    assert_eq!(html.render(), Ok("<p><b>new</b>text</p>".into()));
+    let empty = hteaml!((li = { None::<&str> }));
+    assert_eq!(
+        hteaml::Renderer::new().xml().render(&empty),
+        Ok("<li />".into())
+    );
+}
+
+#[test]
+fn top_level_text() {
+    let name = "<Ferris>";
+    let html = hteaml!("Hello, " (b = {name}) "!");
+    assert_eq!(html.render(), Ok("Hello, <b>&lt;Ferris&gt;</b>!".into()));
+    assert_eq!(hteaml!("a & b"), Html::from("a & b"));
+}
+
+#[test]
+fn comments() {
+    let note = "generated --> do not edit";
+    let html = hteaml! {
+        (!-- "header")
+        (div (!-- {note}) (p = "text"))
+    };
+    assert_eq!(
+        html.render(),
+        Ok("<!-- header --><div><!-- generated - -> do not edit --><p>text</p></div>".into())
+    );
+}
+
 #[test]
 fn html_doc() {
     let doc = hteaml! {
@@ -104,7 +195,7 @@ fn top_level_expr() {
     let html = hteaml! {
         {tag}
     };
-    assert_eq!(html.render(), Ok("<tag>".into()));
+    assert_eq!(html.render(), Ok("<tag></tag>".into()));
 }
 
 #[test]
@@ -114,7 +205,10 @@ fn top_level_expr_mixed() {
         (regular = "content")
         {tag}
     };
-    assert_eq!(html.render(), Ok("<regular>content</regular><tag>".into()));
+    assert_eq!(
+        html.render(),
+        Ok("<regular>content</regular><tag></tag>".into())
+    );
 }
 
 #[test]
@@ -124,7 +218,16 @@ fn top_level_expr_multi() {
     let html = hteaml! {
         {tag} {tag2}
     };
-    assert_eq!(html.render(), Ok("<tag><tag2>".into()));
+    assert_eq!(html.render(), Ok("<tag></tag><tag2></tag2>".into()));
+}
+
+#[test]
+fn tag_content_mixed_multi() {
+    let html = hteaml!((p (b = "bold") (i = "italic") " text " {"expr"} (br)));
+    assert_eq!(
+        html.render(),
+        Ok("<p><b>bold</b><i>italic</i> text expr<br></p>".into())
+    );
 }
 
 #[test]
@@ -134,3 +237,29 @@ fn tag_content_expr_multi() {
     );
     assert_eq!(html.render(), Ok("<tag>onetwo</tag>".into()));
 }
+
+#[test]
+fn escaped_dyn_content() {
+    let user = "<script>alert('pwned')</script>";
+    let html = hteaml!((p title:{user} = {user}));
+    assert_eq!(
+        html.render(),
+        Ok(
+            r#"<p title="<script>alert('pwned')</script>">&lt;script&gt;alert('pwned')&lt;/script&gt;</p>"#
+                .into()
+        )
+    );
+}
+
+#[test]
+fn raw_markup() {
+    let icon = String::from("<svg></svg>");
+    let html = hteaml! {
+        (@raw "<!-- trusted -->")
+        (i = (@raw {icon}) "<svg>")
+    };
+    assert_eq!(
+        html.render(),
+        Ok("<!-- trusted --><i><svg></svg>&lt;svg&gt;</i>".into())
+    );
+}
@@ -0,0 +1,159 @@
+//! Tests for using components in the `hteaml` macro
+use hteaml::{Component, Html, Render};
+use hteaml_macro::hteaml;
+
+struct Card<'a> {
+    title: &'a str,
+    id: u32,
+}
+
+impl<'a> Component<'a> for Card<'a> {
+    fn render(self, children: Html<'a>) -> Html<'a> {
+        hteaml!((section.card data-id:{self.id} (h2 = {self.title}) {children}))
+    }
+}
+
+#[derive(Default)]
+struct Badge {
+    count: usize,
+    hidden: bool,
+}
+
+impl<'a> Component<'a> for Badge {
+    fn render(self, children: Html<'a>) -> Html<'a> {
+        hteaml!((span.badge hidden?:{self.hidden} = {self.count} {children}))
+    }
+}
+
+mod ui {
+    use hteaml::{Component, Html};
+
+    pub struct Divider;
+
+    impl<'a> Component<'a> for Divider {
+        fn render(self, _children: Html<'a>) -> Html<'a> {
+            Html::Raw(hteaml::Raw::new("<hr>"))
+        }
+    }
+}
+
+#[test]
+fn props_and_children() {
+    let title = String::from("Inbox");
+    let html = hteaml! {
+        (@Card title:{&title} id:7
+            (p = "first")
+            "text")
+    };
+    assert_eq!(
+        html.render(),
+        Ok(r#"<section class="card" data-id="7"><h2>Inbox</h2><p>first</p>text</section>"#.into())
+    );
+}
+
+#[test]
+fn shorthand_and_default_props() {
+    let count = 3;
+    let html = hteaml!((p (@Badge count ..) (@Badge hidden:true ..) (@ui::Divider)));
+    assert_eq!(
+        html.render(),
+        Ok(r#"<p><span class="badge">3</span><span class="badge" hidden>0</span><hr></p>"#.into())
+    );
+}
+
+#[test]
+fn nested_components_and_control_flow() {
+    fn list<'a>(items: &[&'a str]) -> Html<'a> {
+        hteaml! {
+            (@Card title:"List" id:1 =
+                @for (i, item) in {items.iter().enumerate()}
+                    (@Badge count:{i} .. = {*item})
+                @if {items.is_empty()} "empty")
+        }
+    }
+    assert_eq!(
+        list(&["a", "b"]).render(),
+        Ok(concat!(
+            r#"<section class="card" data-id="1"><h2>List</h2>"#,
+            r#"<span class="badge">0a</span><span class="badge">1b</span></section>"#
+        )
+        .into())
+    );
+    assert_eq!(
+        list(&[]).render(),
+        Ok(r#"<section class="card" data-id="1"><h2>List</h2>empty</section>"#.into())
+    );
+}
+
+struct Layout<'a> {
+    title: &'a str,
+    slots: LayoutSlots<'a>,
+}
+
+struct LayoutSlots<'a> {
+    header: Html<'a>,
+    sidebar: Option<Html<'a>>,
+    footer: Html<'a>,
+}
+
+impl Default for LayoutSlots<'_> {
+    fn default() -> Self {
+        Self {
+            header: Html::default(),
+            sidebar: None,
+            footer: hteaml!((small = "default footer")),
+        }
+    }
+}
+
+impl<'a> Component<'a> for Layout<'a> {
+    fn render(self, children: Html<'a>) -> Html<'a> {
+        let LayoutSlots {
+            header,
+            sidebar,
+            footer,
+        } = self.slots;
+        hteaml! {
+            (div.layout title:{self.title}
+                (header = {header})
+                @if let Some(sidebar) = {sidebar} (aside = {sidebar})
+                (main = {children})
+                (footer = {footer}))
+        }
+    }
+}
+
+#[test]
+fn named_slots() {
+    let user = "ferris";
+    let html = hteaml! {
+        (@Layout title:"Home" ..slots
+            (:header (h1 = "Welcome") {user})
+            (p = "body")
+            (:sidebar = (@Badge count:2 ..))
+            (:footer "custom footer"))
+    };
+    assert_eq!(
+        html.render(),
+        Ok(concat!(
+            r#"<div class="layout" title="Home"><header><h1>Welcome</h1>ferris</header>"#,
+            r#"<aside><span class="badge">2</span></aside><main><p>body</p></main>"#,
+            r#"<footer>custom footer</footer></div>"#
+        )
+        .into())
+    );
+}
+
+#[test]
+fn required_props_with_default_slots() {
+    let title = "Home";
+    let html = hteaml!((@Layout title ..slots (:header) "body"));
+    assert_eq!(
+        html.render(),
+        Ok(concat!(
+            r#"<div class="layout" title="Home"><header></header><main>body</main>"#,
+            r#"<footer><small>default footer</small></footer></div>"#
+        )
+        .into())
+    );
+}
@@ -0,0 +1,128 @@
+//! Tests for the `@if`, `@for`, `@match` and `@let` control flow syntax of the `hteaml` macro
+use hteaml::{Html, Render};
+use hteaml_macro::hteaml;
+
+#[test]
+fn if_else() {
+    fn nav(user: Option<&str>, admin: bool) -> Html<'_> {
+        hteaml! {
+            (nav
+                @if {admin} (a href:"/admin" = "Admin")
+                @else if {user.is_some()} (a href:"/me" = "Profile")
+                @else (a href:"/login" = "Log in")
+                @if let Some(name) = {user} (span = " " {name})
+            )
+        }
+    }
+    assert_eq!(
+        nav(None, false).render(),
+        Ok(r#"<nav><a href="/login">Log in</a></nav>"#.into())
+    );
+    assert_eq!(
+        nav(Some("ferris"), false).render(),
+        Ok(r#"<nav><a href="/me">Profile</a><span> ferris</span></nav>"#.into())
+    );
+    assert_eq!(
+        nav(Some("root"), true).render(),
+        Ok(r#"<nav><a href="/admin">Admin</a><span> root</span></nav>"#.into())
+    );
+}
+
+#[test]
+fn for_loop() {
+    let rows = [("Tea", 2), ("Cake", 1)];
+    let html = hteaml! {
+        (table
+            @for (item, qty) in {rows.iter()} (tr (td = {*item}) (td = {*qty}))
+        )
+    };
+    assert_eq!(
+        html.render(),
+        Ok("<table><tr><td>Tea</td><td>2</td></tr><tr><td>Cake</td><td>1</td></tr></table>".into())
+    );
+    let empty: [&str; 0] = [];
+    assert_eq!(
+        hteaml!((ul @for x in {empty} (li = {x}))).render(),
+        Ok("<ul></ul>".into())
+    );
+}
+
+#[test]
+fn match_arms() {
+    let status = |code: u16| {
+        hteaml! {
+            (p @match {code} {
+                200 => "ok",
+                c if {c >= 500} => (b = "server error " {c}),
+                _ => {code},
+            })
+        }
+    };
+    assert_eq!(status(200).render(), Ok("<p>ok</p>".into()));
+    assert_eq!(
+        status(503).render(),
+        Ok("<p><b>server error 503</b></p>".into())
+    );
+    assert_eq!(status(404).render(), Ok("<p>404</p>".into()));
+}
+
+#[test]
+fn let_binding() {
+    let items = ["a", "b", "c"];
+    let html = hteaml! {
+        (div
+            @let count: usize = items.len();
+            @let last = items.last().copied().unwrap_or_default();
+            (p = {count} " items, the last is " {last})
+        )
+    };
+    assert_eq!(
+        html.render(),
+        Ok("<div><p>3 items, the last is c</p></div>".into())
+    );
+}
+
+#[test]
+fn hygiene() {
+    let html = "outer";
+    let page = hteaml! {
+        (p
+            @let tag = "inner";
+            {tag} " " {html}
+        )
+        {html}
+    };
+    assert_eq!(page.render(), Ok("<p>inner outer</p>outer".into()));
+}
+
+#[test]
+fn top_level() {
+    let logged_in = false;
+    let html = hteaml! {
+        (h1 = "Title")
+        @if {logged_in} (p = "Welcome back") @else "Please log in"
+        @for i in {1..=2} (hr id:{i})
+    };
+    assert_eq!(
+        html.render(),
+        Ok(r#"<h1>Title</h1>Please log in<hr id="1"><hr id="2">"#.into())
+    );
+    let only = hteaml!(@if {true} (br));
+    assert_eq!(only, Html::Html(vec![hteaml!((br))]));
+}
+
+/// Bodies borrow straight from the loop variables, no nested `hteaml!` calls or owned copies are needed
+fn list(items: &[String]) -> Html<'_> {
+    hteaml! {
+        (ul @for item in {items} (li class:"item" = {item}))
+    }
+}
+
+#[test]
+fn borrowed_data() {
+    let items = vec!["<one>".to_owned(), "two".to_owned()];
+    assert_eq!(
+        list(&items).render(),
+        Ok(r#"<ul><li class="item">&lt;one&gt;</li><li class="item">two</li></ul>"#.into())
+    );
+}
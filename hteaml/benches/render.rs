@@ -0,0 +1,70 @@
+//! Compares rendering a page of text that has to be escaped with rendering the same markup without escaping
+// `criterion_group!` generates an undocumented function
+#![allow(missing_docs)]
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use hteaml::{hteaml, Html, Raw, Render};
+
+/// The rows of the table, with text that is partly escaped: `&` and `<`
+fn rows() -> Vec<(String, String)> {
+    (0..500)
+        .map(|i| {
+            (
+                format!("Tom & Jerry <episode {i}>"),
+                format!("A cat chases a mouse through the house, part {i}, and nothing ever goes to plan."),
+            )
+        })
+        .collect()
+}
+
+/// A listing page such as a search result, `cell` building the content of the table cells
+fn page<'a>(rows: &'a [(String, String)], cell: impl Fn(&'a str) -> Html<'a>) -> Html<'a> {
+    hteaml! {
+        (!doctype html)
+        (html lang:en
+            (head (title = "Episodes") (meta charset:"utf-8"))
+            (body
+                (main.content
+                    (h1 = "Episodes")
+                    (table.listing
+                        (thead (tr (th = "Title") (th = "Summary")))
+                        (tbody
+                            @for (i, (title, summary)) in {rows.iter().enumerate()}
+                            (tr id:{format!("row-{i}")}
+                                (td (a href:{format!("/episodes/{i}")} = {cell(title)}))
+                                (td.summary = {cell(summary)})))))))
+    }
+}
+
+/// Render the page with its cells as text, escaped while rendering, and as already escaped raw markup
+fn escape(c: &mut Criterion) {
+    let rows = rows();
+    let escaped: Vec<_> = rows
+        .iter()
+        .map(|(title, summary)| {
+            (
+                title
+                    .replace('&', "&amp;")
+                    .replace('<', "&lt;")
+                    .replace('>', "&gt;"),
+                summary.clone(),
+            )
+        })
+        .collect();
+    let text = page(&rows, Html::from);
+    let raw = page(&escaped, |s| Raw::new(s).into());
+    assert_eq!(
+        text.render(),
+        raw.render(),
+        "both pages write the same markup"
+    );
+
+    let mut group = c.benchmark_group("render");
+    group.bench_function("escaped", |b| b.iter(|| black_box(&text).render()));
+    group.bench_function("unescaped", |b| b.iter(|| black_box(&raw).render()));
+    group.finish();
+}
+
+criterion_group!(benches, escape);
+criterion_main!(benches);
@@ -0,0 +1,136 @@
+//! A list of classes assembled from parts that can be toggled on and off
+use std::fmt::{self, Display};
+
+use crate::{AttrValue, IntoAttr, IntoStr, Str};
+
+/// A set of classes for the `class` attribute, kept in the order they were added without duplicates
+///
+/// Each part may hold several space separated classes. As an attribute value an empty list leaves the attribute out.
+/// The [`hteaml`](crate::hteaml) macro builds one from the `class:["btn", "btn-primary" => is_primary, {extra}]` syntax.
+///
+/// ## Example
+/// ```
+/// use hteaml::{ClassList, Render, Tag};
+/// let is_primary = false;
+/// let classes = ClassList::new()
+///     .class("btn")
+///     .class_if("btn-primary", is_primary)
+///     .class("btn large");
+/// assert_eq!(classes.to_string(), "btn large");
+///
+/// let tag = Tag::new("button").attr("class", classes);
+/// assert_eq!(tag.render(), Ok(r#"<button class="btn large"></button>"#.into()));
+/// assert_eq!(Tag::new("p").attr("class", ClassList::new()).render(), Ok("<p></p>".into()));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClassList<'a>(Vec<Str<'a>>);
+
+impl<'a> ClassList<'a> {
+    /// Create an empty class list
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add one or more space separated classes, skipping the ones already in the list
+    pub fn class<T: IntoStr<'a>>(mut self, class: T) -> Self {
+        self.push(class);
+        self
+    }
+
+    /// Add one or more space separated classes if `on` is true
+    pub fn class_if<T: IntoStr<'a>>(self, class: T, on: bool) -> Self {
+        match on {
+            true => self.class(class),
+            false => self,
+        }
+    }
+
+    /// Add one or more space separated classes, the in-place counterpart of [`ClassList::class`]
+    pub fn push<T: IntoStr<'a>>(&mut self, class: T) {
+        match class.into_str() {
+            Str::Borrowed(classes) => classes
+                .split_ascii_whitespace()
+                .for_each(|c| self.insert(Str::Borrowed(c))),
+            Str::Owned(classes) => classes
+                .split_ascii_whitespace()
+                .for_each(|c| self.insert(Str::Owned(c.to_owned()))),
+        }
+    }
+
+    fn insert(&mut self, class: Str<'a>) {
+        if !self.contains(&class) {
+            self.0.push(class);
+        }
+    }
+
+    /// Whether `class` is in the list
+    pub fn contains(&self, class: &str) -> bool {
+        self.0.iter().any(|c| c == class)
+    }
+
+    /// Whether the list has no classes
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The classes in the order they were added
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.0.iter().map(|c| &**c)
+    }
+}
+
+impl Display for ClassList<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut classes = self.iter();
+        if let Some(first) = classes.next() {
+            f.write_str(first)?;
+        }
+        classes.try_for_each(|c| write!(f, " {c}"))
+    }
+}
+
+impl<'a, T: IntoStr<'a>> FromIterator<T> for ClassList<'a> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = Self::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<'a, T: IntoStr<'a>> Extend<T> for ClassList<'a> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        iter.into_iter().for_each(|class| self.push(class));
+    }
+}
+
+impl<'a> IntoAttr<'a> for ClassList<'a> {
+    fn into_attr(self) -> Option<AttrValue<'a>> {
+        match self.0.as_slice() {
+            [] => None,
+            [_] => self.0.into_iter().next().map(AttrValue::Str),
+            _ => Some(AttrValue::Str(Str::Owned(self.to_string()))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ClassList, Tag};
+
+    #[test]
+    fn dedup_and_split() {
+        let list: ClassList = ["a b", " c ", "", "b", "d a"].into_iter().collect();
+        assert_eq!(list.iter().collect::<Vec<_>>(), ["a", "b", "c", "d"]);
+        assert_eq!(list.to_string(), "a b c d");
+        assert!(list.contains("c"));
+        assert!(ClassList::new().class_if("x", false).is_empty());
+    }
+
+    #[test]
+    fn merges_with_class_attr() {
+        let tag = Tag::new("p")
+            .attr("class", "a")
+            .attr("class", ClassList::new().class("b a").class_if("c", true));
+        assert_eq!(tag.get_attr("class").as_deref(), Some("a b c"));
+    }
+}
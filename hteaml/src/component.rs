@@ -0,0 +1,34 @@
+//! Reusable pieces of markup taking props and nested content
+use crate::Html;
+
+/// A reusable piece of markup, configured by its fields (the props) and given the content nested in it
+///
+/// Components are usually structs whose fields are the props, used in the [`hteaml`](crate::hteaml) macro as
+/// `(@Card title:{t} (p = "body"))`. The macro builds the struct from the props, so a missing or mistyped prop is a
+/// compile error, and passes the nested nodes as `children`. Fields can also be filled with markup, as named slots
+/// written `(:name ...)` in the macro, see [`hteaml`](crate::hteaml).
+///
+/// ## Example
+/// ```
+/// use hteaml::{hteaml, Component, Html, Render};
+///
+/// struct Card<'a> {
+///     title: &'a str,
+/// }
+///
+/// impl<'a> Component<'a> for Card<'a> {
+///     fn render(self, children: Html<'a>) -> Html<'a> {
+///         hteaml!((div.card (h2 = {self.title}) {children}))
+///     }
+/// }
+///
+/// let html = hteaml!((@Card title:"Hello" (p = "body")));
+/// assert_eq!(html.render(), Ok(r#"<div class="card"><h2>Hello</h2><p>body</p></div>"#.into()));
+///
+/// let html = Card { title: "Hello" }.render(Html::default());
+/// assert_eq!(html.render(), Ok(r#"<div class="card"><h2>Hello</h2></div>"#.into()));
+/// ```
+pub trait Component<'a> {
+    /// Render the component with `children`, the content nested in it, which is empty if there is none
+    fn render(self, children: Html<'a>) -> Html<'a>;
+}
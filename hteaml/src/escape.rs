@@ -0,0 +1,124 @@
+//! HTML escaping used while rendering text nodes and attribute values
+use std::fmt::{self, Write};
+
+/// Write `s` to `w` escaping the characters that are significant inside an HTML text node (`&`, `<`, `>`)
+pub(crate) fn text<W: Write + ?Sized>(w: &mut W, s: &str) -> fmt::Result {
+    escape(w, s, |b| match b {
+        b'&' => Some("&amp;"),
+        b'<' => Some("&lt;"),
+        b'>' => Some("&gt;"),
+        _ => None,
+    })
+}
+
+/// Write `s` to `w` escaping the characters that are significant inside a double quoted attribute value (`&`, `"`)
+pub(crate) fn attr<W: Write + ?Sized>(w: &mut W, s: &str) -> fmt::Result {
+    escape(w, s, |b| match b {
+        b'&' => Some("&amp;"),
+        b'"' => Some("&quot;"),
+        _ => None,
+    })
+}
+
+/// Write `s` to `w` escaping the characters that are not allowed inside a double quoted XML attribute value (`&`, `"`, `<`)
+pub(crate) fn xml_attr<W: Write + ?Sized>(w: &mut W, s: &str) -> fmt::Result {
+    escape(w, s, |b| match b {
+        b'&' => Some("&amp;"),
+        b'"' => Some("&quot;"),
+        b'<' => Some("&lt;"),
+        _ => None,
+    })
+}
+
+/// Write `s` to `w` so it cannot end the comment it is placed in, by putting a space between consecutive dashes
+///
+/// Without `--` the text can contain neither `-->` nor `--!>`, which close a comment, nor `<!--`, which browsers
+/// report as a nested comment.
+pub(crate) fn comment<W: Write + ?Sized>(w: &mut W, s: &str) -> fmt::Result {
+    let mut start = 0;
+    for i in 1..s.len() {
+        if s.as_bytes()[i - 1] == b'-' && s.as_bytes()[i] == b'-' {
+            w.write_str(&s[start..i])?;
+            w.write_char(' ')?;
+            start = i;
+        }
+    }
+    w.write_str(&s[start..])
+}
+
+/// A [`fmt::Write`] adapter escaping everything written to it, used to escape [`fmt::Display`] output as it is written
+pub(crate) struct Escaped<'w, W: Write + ?Sized> {
+    pub(crate) out: &'w mut W,
+    pub(crate) escape: fn(&mut W, &str) -> fmt::Result,
+}
+
+impl<W: Write + ?Sized> Write for Escaped<'_, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        (self.escape)(self.out, s)
+    }
+}
+
+/// Writes unescaped runs of `s` as whole slices, only breaking them up where `entity` returns a replacement
+///
+/// All the escaped characters are ASCII, so slicing at their byte offsets always lands on a char boundary.
+#[inline]
+fn escape<W, F>(w: &mut W, s: &str, entity: F) -> fmt::Result
+where
+    W: Write + ?Sized,
+    F: Fn(u8) -> Option<&'static str>,
+{
+    let mut start = 0;
+    for (i, b) in s.bytes().enumerate() {
+        if let Some(rep) = entity(b) {
+            w.write_str(&s[start..i])?;
+            w.write_str(rep)?;
+            start = i + 1;
+        }
+    }
+    w.write_str(&s[start..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{attr, comment, text, xml_attr};
+
+    fn run(f: fn(&mut String, &str) -> std::fmt::Result, s: &str) -> String {
+        let mut buf = String::new();
+        assert_eq!(f(&mut buf, s), Ok(()));
+        buf
+    }
+
+    #[test]
+    fn text_escaping() {
+        assert_eq!(run(text, "plain"), "plain");
+        assert_eq!(
+            run(text, r#"<script>"a" & 'b'</script>"#),
+            r#"&lt;script&gt;"a" &amp; 'b'&lt;/script&gt;"#
+        );
+    }
+
+    #[test]
+    fn attr_escaping() {
+        assert_eq!(
+            run(attr, r#"x" onclick="alert(1)&<"#),
+            "x&quot; onclick=&quot;alert(1)&amp;<"
+        );
+    }
+
+    #[test]
+    fn xml_attr_escaping() {
+        assert_eq!(run(xml_attr, r#"a<"b">&"#), "a&lt;&quot;b&quot;>&amp;");
+    }
+
+    #[test]
+    fn comment_escaping() {
+        assert_eq!(run(comment, "a - b"), "a - b");
+        assert_eq!(run(comment, "x --> <!-- y --!>"), "x - -> <!- - y - -!>");
+        assert_eq!(run(comment, "----"), "- - - -");
+    }
+
+    #[test]
+    fn multibyte() {
+        assert_eq!(run(text, "ünï<cødé>"), "ünï&lt;cødé&gt;");
+    }
+}
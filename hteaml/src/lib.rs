@@ -1,29 +1,152 @@
 #![doc = include_str!("../../README.md")]
 use std::borrow::Cow;
 use std::fmt::{self, Write};
+use std::io;
+
+use render::{
+    is_block_element, is_boolean_attribute, is_valid_attribute_name, is_void_element,
+    is_whitespace_sensitive,
+};
 
 pub use hteaml_macro::hteaml;
 
+#[cfg(test)]
+extern crate self as hteaml;
+
+mod class;
+mod component;
+mod escape;
+mod parse;
+mod render;
+mod select;
+mod style;
+mod traverse;
+mod value;
+
+pub use class::ClassList;
+pub use component::Component;
+pub use parse::ParseError;
+pub use render::{Formatter, Renderer};
+pub use select::{Selector, SelectorError};
+pub use style::Style;
+pub use traverse::{Bfs, Dfs};
+pub use value::{AttrValue, Fmt, IntoAttr};
+
+/// Helpers used by the code generated by the [`hteaml`] macro, not part of the public API
+#[doc(hidden)]
+pub mod __private {
+    use std::fmt::{self, Write};
+
+    use crate::{Content, Formatter, Html, Render};
+
+    /// The object safe part of [`Render`], so `dyn Render` values can still be rendered
+    ///
+    /// It is implemented for every [`Render`] type and forwards to [`Render::render_fmt`] with a type-erased sink.
+    pub trait RenderDyn {
+        fn render_dyn(&self, f: &mut Formatter<'_, dyn Write + '_>) -> fmt::Result;
+    }
+
+    impl<T: Render> RenderDyn for T {
+        fn render_dyn(&self, f: &mut Formatter<'_, dyn Write + '_>) -> fmt::Result {
+            self.render_fmt(f)
+        }
+    }
+
+    /// Wraps a `{expr}` used as content so the method called on it picks the right conversion
+    ///
+    /// `Wrap(Some(expr)).into_content()` resolves to [`IntoContent`] when `expr` implements `Into<Content>`, since it
+    /// takes the wrapper by value. Otherwise auto-referencing falls back to [`IterContent`], which is implemented on
+    /// `&mut Wrap` for iterators of `Into<Html>` items.
+    pub struct Wrap<T>(pub Option<T>);
+
+    pub trait IntoContent<'a> {
+        fn into_content(self) -> Content<'a>;
+    }
+
+    impl<'a, T: Into<Content<'a>>> IntoContent<'a> for Wrap<T> {
+        fn into_content(self) -> Content<'a> {
+            self.0.map_or_else(Content::default, Into::into)
+        }
+    }
+
+    pub trait IterContent<'a> {
+        fn into_content(self) -> Content<'a>;
+    }
+
+    impl<'a, I> IterContent<'a> for &mut Wrap<I>
+    where
+        I: IntoIterator,
+        I::Item: Into<Html<'a>>,
+    {
+        fn into_content(self) -> Content<'a> {
+            self.0.take().into_iter().flatten().collect()
+        }
+    }
+}
+
 /// The trait through which the provided types (i.e. [`Html`], [`Tag`]) render themselves to HTML
 ///
 /// This trait is implemented on every type that represents an HTML element, which means that each type can also be rendered to a String separately.
 ///
+/// The output can be collected in a `String` or streamed to any [`fmt::Write`] or [`io::Write`] sink (files, sockets, `Vec<u8>`, stdout)
+/// without building an intermediate `String`. These methods write compact HTML, use a [`Renderer`] for other settings such as pretty-printing.
+///
 /// If you wish to make your custom type be directly usable within the [`hteaml`] macro or other types, see [`IntoStr`]
-pub trait Render {
+pub trait Render: __private::RenderDyn {
     /// Render self to HTML
     fn render(&self) -> Result<String, fmt::Error> {
-        let mut buf = String::new();
-        self.render_to_buf(&mut buf)?;
-        Ok(buf)
+        Renderer::new().render(self)
     }
 
-    /// Render self to HTML by writing to the given `String` buffer
-    fn render_to_buf(&self, buf: &mut String) -> fmt::Result;
+    /// Render self to HTML by writing to the given [`fmt::Write`] sink
+    fn render_to_writer<W: Write + ?Sized>(&self, w: &mut W) -> fmt::Result
+    where
+        Self: Sized,
+    {
+        self.render_fmt(&mut Formatter::new(w, Renderer::new()))
+    }
+
+    /// Render self to HTML by writing UTF-8 bytes to the given [`io::Write`] sink
+    ///
+    /// The sink is written to in small chunks as the HTML is rendered, so wrapping unbuffered sinks such as files or sockets
+    /// in an [`io::BufWriter`] is recommended.
+    ///
+    /// ## Example
+    /// ```
+    /// use hteaml::{Render, Tag};
+    /// let mut out = Vec::new();
+    /// Tag::new("p").content("hello").render_to_io(&mut out).unwrap();
+    /// assert_eq!(out, b"<p>hello</p>");
+    /// ```
+    fn render_to_io<W: io::Write + ?Sized>(&self, w: &mut W) -> io::Result<()>
+    where
+        Self: Sized,
+    {
+        Renderer::new().render_to_io(self, w)
+    }
+
+    /// Render self to HTML by writing to the given [`Formatter`], following the settings it carries
+    ///
+    /// This is the only method implementors have to provide, every other method is built on it.
+    /// The generic methods require `Self: Sized`, so `dyn Render` values are rendered with [`Render::render`] or a [`Renderer`].
+    ///
+    /// ## Example
+    /// ```
+    /// use hteaml::{Raw, Render, Renderer, Tag};
+    /// let items: Vec<Box<dyn Render>> = vec![Box::new(Tag::new("br")), Box::new(Raw::new("<hr>"))];
+    /// let out: Vec<_> = items.iter().map(|item| item.render()).collect();
+    /// assert_eq!(out, [Ok("<br>".into()), Ok("<hr>".into())]);
+    /// assert_eq!(Renderer::new().xml().render(&*items[0]), Ok("<br />".into()));
+    /// ```
+    fn render_fmt<W: Write + ?Sized>(&self, f: &mut Formatter<'_, W>) -> fmt::Result
+    where
+        Self: Sized;
 }
 
+/// Strings render as HTML text, so `&`, `<` and `>` are escaped
 impl Render for Str<'_> {
-    fn render_to_buf(&self, buf: &mut String) -> fmt::Result {
-        buf.write_str(self)
+    fn render_fmt<W: Write + ?Sized>(&self, f: &mut Formatter<'_, W>) -> fmt::Result {
+        f.write_text(self)
     }
 }
 
@@ -64,6 +187,12 @@ impl<'a> IntoStr<'a> for String {
     }
 }
 
+impl<'a> IntoStr<'a> for Str<'a> {
+    fn into_str(self) -> Str<'a> {
+        self
+    }
+}
+
 impl<'a> IntoStr<'a> for &'a str {
     fn into_str(self) -> Str<'a> {
         Cow::Borrowed(self)
@@ -84,15 +213,21 @@ impl<'a, T: AsRef<str>> IntoStr<'a> for &'a T {
 /// If you wish to make your custom type be directly usable within the [`hteaml`] macro or other types, see [`IntoStr`]
 pub type Str<'a> = Cow<'a, str>;
 
-/// Top level representation of HTML markup which can contain a single tag or a comment or a sequence of both
+/// Top level representation of HTML markup which can contain a single tag, text, a comment, a doctype, trusted raw markup or a sequence of them
 ///
 /// The [`hteaml`] macro returns this type on every invocation
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Html<'a> {
     /// An HTML tag
     Tag(Tag<'a>),
+    /// Plain text, which is escaped when rendered
+    Text(Str<'a>),
     /// An HTML comment
     Comment(Comment<'a>),
+    /// A document type declaration
+    Doctype(Doctype<'a>),
+    /// Trusted markup that is rendered without escaping
+    Raw(Raw<'a>),
     /// A sequence containing tags and comments or more nested sequences
     Html(Vec<Html<'a>>),
 }
@@ -103,36 +238,259 @@ impl<'a> From<Tag<'a>> for Html<'a> {
     }
 }
 
+impl<'a, T> From<T> for Html<'a>
+where
+    T: IntoStr<'a>,
+{
+    fn from(value: T) -> Self {
+        Self::Text(value.into_str())
+    }
+}
+
 impl<'a> From<Comment<'a>> for Html<'a> {
     fn from(value: Comment<'a>) -> Self {
         Self::Comment(value)
     }
 }
 
+impl<'a> From<Doctype<'a>> for Html<'a> {
+    fn from(value: Doctype<'a>) -> Self {
+        Self::Doctype(value)
+    }
+}
+
+impl<'a> From<Raw<'a>> for Html<'a> {
+    fn from(value: Raw<'a>) -> Self {
+        Self::Raw(value)
+    }
+}
+
 impl<'a> From<Vec<Html<'a>>> for Html<'a> {
     fn from(value: Vec<Html<'a>>) -> Self {
         Self::Html(value)
     }
 }
 
+/// An empty sequence, which renders nothing
+impl Default for Html<'_> {
+    fn default() -> Self {
+        Self::Html(vec![])
+    }
+}
+
+/// `None` becomes an empty sequence, which renders nothing
+impl<'a, T: Into<Html<'a>>> From<Option<T>> for Html<'a> {
+    fn from(value: Option<T>) -> Self {
+        value.map_or_else(|| Self::Html(vec![]), Into::into)
+    }
+}
+
+impl<'a, T: Into<Html<'a>>> FromIterator<T> for Html<'a> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::Html(iter.into_iter().map(Into::into).collect())
+    }
+}
+
+/// Appends every node with [`Html::push`]
+impl<'a, T: Into<Html<'a>>> Extend<T> for Html<'a> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        iter.into_iter().for_each(|node| self.push(node));
+    }
+}
+
 impl Render for Html<'_> {
-    fn render_to_buf(&self, buf: &mut String) -> fmt::Result {
+    fn render_fmt<W: Write + ?Sized>(&self, f: &mut Formatter<'_, W>) -> fmt::Result {
         match self {
-            Html::Tag(t) => t.render_to_buf(buf),
-            Html::Comment(c) => c.render_to_buf(buf),
-            Html::Html(h) => h.iter().try_for_each(|e| e.render_to_buf(buf)),
+            Html::Tag(t) => t.render_fmt(f),
+            Html::Text(s) => f.write_text(s),
+            Html::Comment(c) => c.render_fmt(f),
+            Html::Doctype(d) => d.render_fmt(f),
+            Html::Raw(r) => r.render_fmt(f),
+            Html::Html(h)
+                if f.is_pretty()
+                    && f.is_block()
+                    && is_block_layout(h, Html::is_block, Html::is_blank) =>
+            {
+                h.iter()
+                    .filter(|e| !e.is_blank())
+                    .enumerate()
+                    .try_for_each(|(i, e)| {
+                        if i > 0 {
+                            f.newline()?;
+                        }
+                        e.render_fmt(f)
+                    })
+            }
+            Html::Html(h) => h.iter().try_for_each(|e| e.render_fmt(f)),
         }
     }
 }
 
+impl<'a> Html<'a> {
+    /// The tag, if this node is one
+    pub fn as_tag(&self) -> Option<&Tag<'a>> {
+        match self {
+            Html::Tag(t) => Some(t),
+            _ => None,
+        }
+    }
+
+    /// The text, if this node is a text node
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Html::Text(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Depth-first (pre-order) iterator over this node and all the nodes nested in it
+    ///
+    /// [`Html::Html`] sequences are transparent: they are not yielded themselves, only the nodes in them are.
+    ///
+    /// ## Example
+    /// ```
+    /// use hteaml::hteaml;
+    /// let html = hteaml! {
+    ///     (nav (a href:"/" = "Home") (a href:"/about" = "About"))
+    ///     (main (h1 = "Hi"))
+    /// };
+    /// let names: Vec<_> = html.dfs().filter_map(|h| h.as_tag()).map(|t| t.name()).collect();
+    /// assert_eq!(names, ["nav", "a", "a", "main", "h1"]);
+    /// let links: Vec<_> = html.tags().filter_map(|t| t.get_attr("href")).collect();
+    /// assert_eq!(links, ["/", "/about"]);
+    /// ```
+    pub fn dfs(&self) -> Dfs<'_, 'a> {
+        Dfs::new(std::iter::once(self))
+    }
+
+    /// Breadth-first (level-order) iterator over this node and all the nodes nested in it
+    ///
+    /// [`Html::Html`] sequences are transparent: they are not yielded themselves, only the nodes in them are.
+    pub fn bfs(&self) -> Bfs<'_, 'a> {
+        Bfs::new(std::iter::once(self))
+    }
+
+    /// Depth-first iterator over all the tags in this tree
+    pub fn tags(&self) -> impl Iterator<Item = &Tag<'a>> {
+        self.dfs().filter_map(Html::as_tag)
+    }
+
+    /// Mutable access to the tag, if this node is one
+    pub fn as_tag_mut(&mut self) -> Option<&mut Tag<'a>> {
+        match self {
+            Html::Tag(t) => Some(t),
+            _ => None,
+        }
+    }
+
+    /// Call `f` on every tag in this tree, in depth-first order
+    ///
+    /// ## Example
+    /// ```
+    /// use hteaml::{hteaml, Render};
+    /// let mut nav = hteaml!((nav (a href:"/" = "Home") (a href:"/blog" = "Blog")));
+    /// nav.for_each_tag_mut(|tag| {
+    ///     if tag.name() == "a" && tag.get_attr("href").as_deref() == Some("/blog") {
+    ///         tag.add_class("active");
+    ///     }
+    /// });
+    /// assert_eq!(
+    ///     nav.render(),
+    ///     Ok(r#"<nav><a href="/">Home</a><a href="/blog" class="active">Blog</a></nav>"#.into())
+    /// );
+    /// ```
+    pub fn for_each_tag_mut<F: FnMut(&mut Tag<'a>)>(&mut self, mut f: F) {
+        self.for_each_tag_mut_inner(&mut f);
+    }
+
+    fn for_each_tag_mut_inner<F: FnMut(&mut Tag<'a>)>(&mut self, f: &mut F) {
+        match self {
+            Html::Tag(t) => {
+                f(t);
+                t.content
+                    .iter_mut()
+                    .filter_map(Content::as_html_mut)
+                    .for_each(|h| h.for_each_tag_mut_inner(f));
+            }
+            Html::Html(seq) => seq.iter_mut().for_each(|h| h.for_each_tag_mut_inner(f)),
+            _ => {}
+        }
+    }
+
+    /// The first tag in this tree (in depth-first order) for which `pred` returns true
+    pub fn find_tag_mut<P: FnMut(&Tag<'a>) -> bool>(
+        &mut self,
+        mut pred: P,
+    ) -> Option<&mut Tag<'a>> {
+        self.find_tag_mut_inner(&mut pred)
+    }
+
+    fn find_tag_mut_inner<P: FnMut(&Tag<'a>) -> bool>(
+        &mut self,
+        pred: &mut P,
+    ) -> Option<&mut Tag<'a>> {
+        match self {
+            Html::Tag(t) => {
+                if pred(t) {
+                    return Some(t);
+                }
+                t.content
+                    .iter_mut()
+                    .filter_map(Content::as_html_mut)
+                    .find_map(|h| h.find_tag_mut_inner(pred))
+            }
+            Html::Html(seq) => seq.iter_mut().find_map(|h| h.find_tag_mut_inner(pred)),
+            _ => None,
+        }
+    }
+
+    /// Append a node after this one, turning this node into an [`Html::Html`] sequence if it isn't one already
+    pub fn push<H: Into<Html<'a>>>(&mut self, node: H) {
+        match self {
+            Html::Html(seq) => seq.push(node.into()),
+            single => {
+                let first = std::mem::replace(single, Html::Html(vec![]));
+                *single = Html::Html(vec![first, node.into()]);
+            }
+        }
+    }
+
+    /// Whether this node can be placed on its own line when pretty-printing
+    fn is_block(&self) -> bool {
+        match self {
+            Html::Tag(t) => is_block_element(&t.name),
+            Html::Comment(_) | Html::Doctype(_) => true,
+            Html::Text(_) | Html::Raw(_) => false,
+            Html::Html(h) => h.iter().all(Html::is_block),
+        }
+    }
+
+    /// Whether this node renders nothing at all
+    fn is_blank(&self) -> bool {
+        match self {
+            Html::Text(s) => s.bytes().all(|b| b.is_ascii_whitespace()),
+            Html::Html(h) => h.iter().all(Html::is_blank),
+            _ => false,
+        }
+    }
+}
+
+/// Whether `nodes` can be spread over separate lines, i.e. they are all block-level and at least one renders something
+fn is_block_layout<T>(nodes: &[T], is_block: fn(&T) -> bool, is_blank: fn(&T) -> bool) -> bool {
+    nodes.iter().all(is_block) && !nodes.iter().all(is_blank)
+}
+
 /// Type that represents an HTML comment
 ///
-/// Note: Comments are still not supported in the [`hteaml`] macro
+/// In the [`hteaml`] macro it is written as `(!-- "text")` or `(!-- {expr})`.
+/// Consecutive dashes in the text are separated by a space when rendered, so the text can never close the comment early.
 ///
 /// ## Example
 /// ```
-/// use hteaml::Render;
-/// assert_eq!(hteaml::Comment::new("comment").render(), Ok("<!-- comment -->".into()))
+/// use hteaml::{Comment, Render, hteaml};
+/// assert_eq!(Comment::new("comment").render(), Ok("<!-- comment -->".into()));
+/// assert_eq!(Comment::new("a --> b").render(), Ok("<!-- a - -> b -->".into()));
+/// assert_eq!(hteaml!((!-- "comment")), Comment::new("comment").into());
 /// ```
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Comment<'a>(Str<'a>);
@@ -142,11 +500,142 @@ impl<'a> Comment<'a> {
     pub fn new<T: IntoStr<'a>>(comment: T) -> Self {
         Self(comment.into_str())
     }
+
+    /// The text of the comment
+    pub fn text(&self) -> &str {
+        &self.0
+    }
 }
 
 impl Render for Comment<'_> {
-    fn render_to_buf(&self, buf: &mut String) -> fmt::Result {
-        write!(buf, "<!-- {} -->", self.0)
+    fn render_fmt<W: Write + ?Sized>(&self, f: &mut Formatter<'_, W>) -> fmt::Result {
+        f.write_str("<!-- ")?;
+        f.write_comment(&self.0)?;
+        f.write_str(" -->")
+    }
+}
+
+/// Type that represents a document type declaration, e.g. `<!DOCTYPE html>`
+///
+/// In the [`hteaml`] macro it is written as `(!doctype html)`. Legacy documents such as XHTML 1.0 can set the
+/// public and system identifiers, written as `(!doctype html public:"..." system:"...")` in the macro.
+///
+/// ## Example
+/// ```
+/// use hteaml::{Doctype, Render, hteaml};
+/// assert_eq!(Doctype::new("html").render(), Ok("<!DOCTYPE html>".into()));
+///
+/// let xhtml = Doctype::new("html")
+///     .public_id("-//W3C//DTD XHTML 1.0 Strict//EN")
+///     .system_id("http://www.w3.org/TR/xhtml1/DTD/xhtml1-strict.dtd");
+/// assert_eq!(
+///     xhtml.render(),
+///     Ok(r#"<!DOCTYPE html PUBLIC "-//W3C//DTD XHTML 1.0 Strict//EN" "http://www.w3.org/TR/xhtml1/DTD/xhtml1-strict.dtd">"#.into())
+/// );
+/// assert_eq!(hteaml!((!doctype html)), Doctype::new("html").into());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Doctype<'a> {
+    name: Str<'a>,
+    public_id: Option<Str<'a>>,
+    system_id: Option<Str<'a>>,
+}
+
+impl<'a> Doctype<'a> {
+    /// Construct a new doctype for the given root element, which is `html` for all HTML documents
+    pub fn new<T: IntoStr<'a>>(name: T) -> Self {
+        Self {
+            name: name.into_str(),
+            public_id: None,
+            system_id: None,
+        }
+    }
+
+    /// Set the public identifier, e.g. `-//W3C//DTD XHTML 1.0 Strict//EN`
+    pub fn public_id<T: IntoStr<'a>>(mut self, id: T) -> Self {
+        self.public_id = Some(id.into_str());
+        self
+    }
+
+    /// Set the system identifier, i.e. the URL of the DTD
+    pub fn system_id<T: IntoStr<'a>>(mut self, id: T) -> Self {
+        self.system_id = Some(id.into_str());
+        self
+    }
+
+    /// The name of the root element
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The public identifier, if any
+    pub fn get_public_id(&self) -> Option<&str> {
+        self.public_id.as_deref()
+    }
+
+    /// The system identifier, if any
+    pub fn get_system_id(&self) -> Option<&str> {
+        self.system_id.as_deref()
+    }
+}
+
+impl Render for Doctype<'_> {
+    fn render_fmt<W: Write + ?Sized>(&self, f: &mut Formatter<'_, W>) -> fmt::Result {
+        f.write_str("<!DOCTYPE")?;
+        if !self.name.is_empty() {
+            write!(f, " {}", self.name)?;
+        }
+        match (&self.public_id, &self.system_id) {
+            (Some(public), system) => {
+                f.write_str(r#" PUBLIC ""#)?;
+                f.write_attr_value(public)?;
+                if let Some(system) = system {
+                    f.write_str(r#"" ""#)?;
+                    f.write_attr_value(system)?;
+                }
+                f.write_char('"')?;
+            }
+            (None, Some(system)) => {
+                f.write_str(r#" SYSTEM ""#)?;
+                f.write_attr_value(system)?;
+                f.write_char('"')?;
+            }
+            (None, None) => {}
+        }
+        f.write_char('>')
+    }
+}
+
+/// Markup that is trusted to be valid and safe, so it is rendered as is without any escaping
+///
+/// Use this for HTML that is already escaped or comes from a trusted source, such as a sanitized CMS fragment or an SVG icon.
+/// In the [`hteaml`] macro it is written as `(@raw "markup")` or `(@raw {expr})`, which keeps the unescaped parts of a template easy to audit.
+///
+/// ## Example
+/// ```
+/// use hteaml::{Raw, Render, Tag};
+/// let icon = Raw::new("<svg></svg>");
+/// assert_eq!(Tag::new("i").content(icon).render(), Ok("<i><svg></svg></i>".into()));
+/// assert_eq!(Tag::new("i").content("<svg></svg>").render(), Ok("<i>&lt;svg&gt;&lt;/svg&gt;</i>".into()));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Raw<'a>(Str<'a>);
+
+impl<'a> Raw<'a> {
+    /// Wrap trusted markup
+    pub fn new<T: IntoStr<'a>>(markup: T) -> Self {
+        Self(markup.into_str())
+    }
+
+    /// The wrapped markup
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Render for Raw<'_> {
+    fn render_fmt<W: Write + ?Sized>(&self, f: &mut Formatter<'_, W>) -> fmt::Result {
+        f.write_str(&self.0)
     }
 }
 
@@ -155,16 +644,23 @@ impl Render for Comment<'_> {
 /// This is the building block for HTML. A tag can be created either directly through the provided builder
 /// pattern or using the [`hteaml`] macro
 ///
-/// > Note: calling `.self_closing()` on the tag type will ignore any content (if it was provided).
+/// HTML5 void elements (e.g. `br`, `img`, `input`) are recognized by their name and rendered without an end tag,
+/// every other tag always gets one, even when it has no content: `<div></div>`.
+///
+/// > Note: void elements and tags made `.self_closing()` cannot have content, rendering them with content fails
+/// > instead of silently dropping it. Use [`Tag::try_content`] to catch this while building the tag, the error of
+/// > [`Render::render_to_io`] also names the tag at fault.
 ///
 /// ## Example
 /// ```
-/// use hteaml::{Html, Tag, hteaml};   
+/// use hteaml::{Html, Render, Tag, hteaml};
 /// let tag = Tag::new("div").attr("key","val").content("content");
 /// assert_eq!(Html::Tag(tag), hteaml!((div key:val = "content")));
 ///
-/// let tag = Tag::new("br").self_closing();
+/// let tag = Tag::new("br");
+/// assert_eq!(tag.render(), Ok("<br>".into()));
 /// assert_eq!(Html::Tag(tag), hteaml!((br)));
+/// assert_eq!(hteaml!((div)).render(), Ok("<div></div>".into()));
 /// ```
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Tag<'a> {
@@ -175,19 +671,47 @@ pub struct Tag<'a> {
 }
 
 impl Render for Tag<'_> {
-    fn render_to_buf(&self, buf: &mut String) -> fmt::Result {
-        write!(buf, "<{}", self.name)?;
+    fn render_fmt<W: Write + ?Sized>(&self, f: &mut Formatter<'_, W>) -> fmt::Result {
+        write!(f, "<{}", self.name)?;
         self.attributes.iter().try_for_each(|attr| -> fmt::Result {
-            buf.write_char(' ')?;
-            attr.render_to_buf(buf)?;
+            f.write_char(' ')?;
+            attr.render_fmt(f)?;
             Ok(())
         })?;
-        if self.self_closing {
-            return write!(buf, ">");
+        if self.self_closing || self.is_void() {
+            if !self.content.is_empty() {
+                return f.misplaced_content(&self.name);
+            }
+            return f.write_str(if f.is_xml() { " />" } else { ">" });
+        }
+        if f.is_xml() && self.content.is_empty() {
+            return f.write_str(" />");
         }
-        buf.write_char('>')?;
-        self.content.iter().try_for_each(|c| c.render_to_buf(buf))?;
-        write!(buf, "</{name}>", name = self.name)
+        f.write_char('>')?;
+        if is_whitespace_sensitive(&self.name) {
+            f.verbatim(|f| self.content.iter().try_for_each(|c| c.render_fmt(f)))?;
+        } else if f.is_pretty()
+            && is_block_element(&self.name)
+            && is_block_layout(&self.content, Content::is_block, Content::is_blank)
+        {
+            f.indented(|f| {
+                f.layout(true, |f| {
+                    self.content
+                        .iter()
+                        .filter(|c| !c.is_blank())
+                        .try_for_each(|c| {
+                            f.newline()?;
+                            c.render_fmt(f)
+                        })
+                })
+            })?;
+            f.newline()?;
+        } else {
+            f.layout(false, |f| {
+                self.content.iter().try_for_each(|c| c.render_fmt(f))
+            })?;
+        }
+        write!(f, "</{name}>", name = self.name)
     }
 }
 
@@ -205,74 +729,424 @@ impl<'a> Tag<'a> {
         }
     }
 
-    /// Append a tag attribute to the tag
+    /// Add an attribute to the tag
+    ///
+    /// If the tag already has an attribute with the same key its value is replaced (see [`Tag::set_attr`]),
+    /// except for `class` where the new classes are appended to the existing ones (see [`Tag::add_class`]).
+    ///
+    /// The key accepts any type that implements the trait [`IntoStr`].
+    /// The [`IntoStr`] trait is implemented for `&str`, `String and any type that implements `AsRef<str>`.
+    /// The value accepts the same types as well as numbers, `bool`, `char` and [`Fmt`] values, see [`AttrValue`].
+    /// It can also be an `Option` of those, in which case `None` leaves the attribute out, see [`IntoAttr`].
+    /// Keys that aren't valid attribute names are ignored, see [`Tag::set_attr`].
     ///
-    /// The generic parameters accept any type that implements the trait [`IntoStr`].
-    /// The [`IntoStr`] trait is implemented for `&str`, `String and any type that implements `AsRef<str>`
+    /// The last value given for a key wins, and a value that leaves the attribute out counts too: `None`, or `false`
+    /// for a boolean attribute, removes the attribute if the tag already has it. `class` is the exception, where
+    /// `None` adds no class and keeps the existing ones.
+    ///
+    /// A `bool` value turns HTML boolean attributes such as `disabled` on and off like [`Tag::bool_attr`], other
+    /// attributes are written `true` or `false`.
+    ///
+    /// ## Example
+    /// ```
+    /// use hteaml::{Render, Tag};
+    /// let tag = Tag::new("button").attr("disabled", false).attr("hidden", true).attr("aria-pressed", false);
+    /// assert_eq!(tag.render(), Ok(r#"<button hidden aria-pressed="false"></button>"#.into()));
+    /// ```
     pub fn attr<A, B>(mut self, key: A, val: B) -> Self
     where
         A: IntoStr<'a>,
-        B: IntoStr<'a>,
+        B: IntoAttr<'a>,
     {
-        self.attributes.push(Attr {
-            key: key.into_str(),
-            val: val.into_str(),
-        });
+        let key = key.into_str();
+        let Some(val) = val.into_attr() else {
+            if key != "class" {
+                self.remove_attr(&key);
+            }
+            return self;
+        };
+        match val {
+            _ if key == "class" => self.add_class(val.into_str()),
+            AttrValue::Fmt(Fmt::Bool(on)) if is_boolean_attribute(&key) => match on {
+                true => self.set_attr(key, ""),
+                false => {
+                    self.remove_attr(&key);
+                }
+            },
+            val => self.set_attr(key, val),
+        }
         self
     }
 
+    /// Add several attributes, as if [`Tag::attr`] was called on each key and value pair in order
+    ///
+    /// Any iterator of pairs can be given, such as a `Vec`, a `BTreeMap` or a `HashMap` (whose order isn't defined).
+    /// Keys accept the types accepted by [`Tag::attr`] and so do values, including `Option` values which leave the
+    /// attribute out when they are `None`.
+    ///
+    /// When keys collide the last value wins: an attribute set again keeps its position but takes the new value, both
+    /// for keys repeated in `attrs` and for keys the tag already has. A `None` value, or `false` for a boolean
+    /// attribute, removes the attribute instead, see [`Tag::attr`]. Classes are merged instead of replaced.
+    /// Keys that aren't valid attribute names are ignored, so maps coming from untrusted data can't inject markup.
+    ///
+    /// ## Example
+    /// ```
+    /// use hteaml::{Render, Tag};
+    /// use std::collections::BTreeMap;
+    /// let extra = BTreeMap::from([("class", "wide"), ("data-id", "7"), ("type", "submit")]);
+    /// let tag = Tag::new("button")
+    ///     .attr("type", "button")
+    ///     .attr("class", "btn")
+    ///     .attrs(extra)
+    ///     .attrs([("aria-label", Some("Go")), ("data-id", None)]);
+    /// assert_eq!(
+    ///     tag.render(),
+    ///     Ok(r#"<button type="submit" class="btn wide" aria-label="Go"></button>"#.into())
+    /// );
+    /// ```
+    pub fn attrs<I, A, B>(self, attrs: I) -> Self
+    where
+        I: IntoIterator<Item = (A, B)>,
+        A: IntoStr<'a>,
+        B: IntoAttr<'a>,
+    {
+        attrs
+            .into_iter()
+            .fold(self, |tag, (key, val)| tag.attr(key, val))
+    }
+
+    /// Add an attribute without a value if `on` is true, or leave it out if it is false
+    ///
+    /// This is meant for boolean attributes such as `disabled`, `checked` or `selected`, which are turned on by
+    /// being present whatever their value is.
+    ///
+    /// ## Example
+    /// ```
+    /// use hteaml::{Render, Tag};
+    /// let tag = Tag::new("input").bool_attr("checked", true).bool_attr("disabled", false);
+    /// assert_eq!(tag.render(), Ok("<input checked>".into()));
+    /// ```
+    pub fn bool_attr<A: IntoStr<'a>>(self, key: A, on: bool) -> Self {
+        self.attr(key, on.then_some(""))
+    }
+
     /// Append content to the tag
     ///
     /// The `content` parameter accepts any type that implements `Into<Content>`.
     /// All types that implement [`IntoStr`] also implement [`Into<Content>`], which means all string types and others that implement `AsRef<str>`.
+    /// Numbers, `bool`, `char` and [`Fmt`] values are formatted straight into the output when the tag is rendered.
     /// Other types that implement `Into<Content>` are the types in the [`Html`] enum variants, and the enum itself.
+    ///
+    /// Empty sequences, such as the content made from `None`, are left out so the tag stays empty.
     pub fn content<C: Into<Content<'a>>>(mut self, content: C) -> Self {
-        self.content.push(content.into());
+        self.push_child(content);
         self
     }
 
+    /// Append content to the tag, failing if the tag is a void element or self-closing since those cannot have content
+    ///
+    /// ## Example
+    /// ```
+    /// use hteaml::Tag;
+    /// assert!(Tag::new("p").try_content("text").is_ok());
+    /// assert_eq!(
+    ///     Tag::new("br").try_content("text").map_err(|e| e.to_string()),
+    ///     Err("void element `br` cannot have content".into())
+    /// );
+    /// ```
+    pub fn try_content<C: Into<Content<'a>>>(self, content: C) -> Result<Self, VoidElementError> {
+        if self.self_closing || self.is_void() {
+            return Err(VoidElementError {
+                name: self.name.into_owned(),
+            });
+        }
+        Ok(self.content(content))
+    }
+
     /// Make the tag self-enclosing (i.e. single tag without an additional closing tag)
     ///
-    /// Note: self closing tags do not contain any content, therefore any content appended to the tag will be ignored.
+    /// HTML5 void elements are already rendered this way, this is meant for other elements that must be written without an end tag.
+    ///
+    /// Note: self closing tags do not contain any content, rendering one with content fails.
     pub fn self_closing(mut self) -> Self {
         self.self_closing = true;
         self
     }
+
+    /// The name of the tag
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The attributes of the tag in the order they were added
+    pub fn attributes(&self) -> &[Attr<'a>] {
+        &self.attributes
+    }
+
+    /// The value of the first attribute named `key` as text, if there is one
+    ///
+    /// Attributes without a value return `Some("")`. Values that are not strings, such as numbers or concatenated
+    /// values, are formatted, see [`AttrValue::to_str`].
+    ///
+    /// ## Example
+    /// ```
+    /// use hteaml::Tag;
+    /// let tag = Tag::new("td").attr("colspan", 2).attr("title", "total");
+    /// assert_eq!(tag.get_attr("colspan").as_deref(), Some("2"));
+    /// assert_eq!(tag.get_attr("title").as_deref(), Some("total"));
+    /// ```
+    pub fn get_attr(&self, key: &str) -> Option<Str<'_>> {
+        self.attributes
+            .iter()
+            .find(|a| a.key == key)
+            .map(|a| a.val.to_str())
+    }
+
+    /// The content of the tag, i.e. its child nodes and text
+    pub fn children(&self) -> &[Content<'a>] {
+        &self.content
+    }
+
+    /// Whether the tag was made self-closing with [`Tag::self_closing`]
+    pub fn is_self_closing(&self) -> bool {
+        self.self_closing
+    }
+
+    /// Whether the tag is an HTML5 void element (e.g. `br`, `img`, `input`), which is written without an end tag
+    pub fn is_void(&self) -> bool {
+        is_void_element(&self.name)
+    }
+
+    /// Depth-first iterator over the nodes nested in this tag, see [`Html::dfs`]
+    pub fn descendants(&self) -> Dfs<'_, 'a> {
+        Dfs::new(self.content.iter().filter_map(Content::as_html))
+    }
+
+    /// Whether the tag has an attribute named `key`
+    pub fn has_attr(&self, key: &str) -> bool {
+        self.attributes.iter().any(|a| a.key == key)
+    }
+
+    /// Set the value of the attribute `key`, adding it if the tag doesn't have it yet
+    ///
+    /// An existing attribute keeps its position, any duplicates of it are removed.
+    ///
+    /// Keys are written without escaping, so a key that isn't a valid attribute name is ignored: empty keys and keys
+    /// containing whitespace, quotes, `<`, `>`, `/`, `=` or control characters.
+    pub fn set_attr<A, B>(&mut self, key: A, val: B)
+    where
+        A: IntoStr<'a>,
+        B: Into<AttrValue<'a>>,
+    {
+        let key = key.into_str();
+        if !is_valid_attribute_name(&key) {
+            return;
+        }
+        let val = val.into();
+        match self.attributes.iter().position(|a| a.key == key) {
+            Some(i) => {
+                self.attributes[i].val = val;
+                let mut seen = false;
+                self.attributes
+                    .retain(|a| a.key != key || !std::mem::replace(&mut seen, true));
+            }
+            None => self.attributes.push(Attr { key, val }),
+        }
+    }
+
+    /// Remove the attribute `key` (including any duplicates), returning its value if the tag had it
+    pub fn remove_attr(&mut self, key: &str) -> Option<AttrValue<'a>> {
+        let i = self.attributes.iter().position(|a| a.key == key)?;
+        let removed = self.attributes.remove(i);
+        self.attributes.retain(|a| a.key != key);
+        Some(removed.val)
+    }
+
+    /// Whether `class` is one of the classes in the `class` attribute
+    pub fn has_class(&self, class: &str) -> bool {
+        self.get_attr("class")
+            .is_some_and(|classes| classes.split_ascii_whitespace().any(|c| c == class))
+    }
+
+    /// Add one or more space separated classes to the `class` attribute, skipping the ones the tag already has
+    pub fn add_class<T: IntoStr<'a>>(&mut self, class: T) {
+        let class = class.into_str();
+        let Some(attr) = self.attributes.iter_mut().find(|a| a.key == "class") else {
+            return self.attributes.push(Attr {
+                key: "class".into(),
+                val: AttrValue::Str(class),
+            });
+        };
+        for c in class.split_ascii_whitespace() {
+            if !attr
+                .val
+                .to_str()
+                .split_ascii_whitespace()
+                .any(|existing| existing == c)
+            {
+                let val = attr.val.to_mut();
+                if !val.is_empty() {
+                    val.push(' ');
+                }
+                val.push_str(c);
+            }
+        }
+    }
+
+    /// Remove `class` from the `class` attribute, dropping the attribute if no classes are left
+    pub fn remove_class(&mut self, class: &str) {
+        let Some(i) = self.attributes.iter().position(|a| a.key == "class") else {
+            return;
+        };
+        let attr = &mut self.attributes[i];
+        let classes = attr.val.to_str();
+        if !classes.split_ascii_whitespace().any(|c| c == class) {
+            return;
+        }
+        let rest = classes
+            .split_ascii_whitespace()
+            .filter(|&c| c != class)
+            .collect::<Vec<_>>()
+            .join(" ");
+        if rest.is_empty() {
+            self.attributes.remove(i);
+        } else {
+            attr.val = AttrValue::Str(Cow::Owned(rest));
+        }
+    }
+
+    /// Mutable access to the content of the tag
+    pub fn children_mut(&mut self) -> &mut Vec<Content<'a>> {
+        &mut self.content
+    }
+
+    /// Append content to the tag, the in-place counterpart of [`Tag::content`]
+    pub fn push_child<C: Into<Content<'a>>>(&mut self, content: C) {
+        match content.into() {
+            Content::Html(Html::Html(nodes)) if nodes.is_empty() => {}
+            content => self.content.push(content),
+        }
+    }
+
+    /// Insert content at position `index` among the children, shifting the ones after it
+    ///
+    /// If `index` is past the end the content is appended.
+    pub fn insert_child<C: Into<Content<'a>>>(&mut self, index: usize, content: C) {
+        self.content
+            .insert(index.min(self.content.len()), content.into());
+    }
+
+    /// Remove and return the child at position `index`, if there is one
+    pub fn remove_child(&mut self, index: usize) -> Option<Content<'a>> {
+        (index < self.content.len()).then(|| self.content.remove(index))
+    }
+}
+
+/// The error returned when adding content to a void element or a self-closing tag, see [`Tag::try_content`]
+///
+/// [`Render::render_to_io`] also reports it when rendering such a tag with content, wrapped in an [`io::Error`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VoidElementError {
+    pub(crate) name: String,
+}
+
+impl VoidElementError {
+    /// The name of the tag the content was added to
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl fmt::Display for VoidElementError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "void element `{}` cannot have content", self.name)
+    }
 }
 
+impl std::error::Error for VoidElementError {}
+
 /// Represents an HTML tag attribute
+///
+/// The value is escaped when rendered, the key is written as is.
 #[derive(Debug, Clone, PartialEq, Eq)]
-struct Attr<'a> {
+pub struct Attr<'a> {
     key: Str<'a>,
-    val: Str<'a>,
+    val: AttrValue<'a>,
+}
+
+impl<'a> Attr<'a> {
+    /// The name of the attribute
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// The value of the attribute, which is an empty string for attributes written without a value
+    pub fn value(&self) -> &AttrValue<'a> {
+        &self.val
+    }
 }
 
 impl Render for Attr<'_> {
-    fn render_to_buf(&self, buf: &mut String) -> fmt::Result {
-        if self.val.is_empty() {
-            return write!(buf, "{key}", key = self.key);
+    fn render_fmt<W: Write + ?Sized>(&self, f: &mut Formatter<'_, W>) -> fmt::Result {
+        if self.val.is_empty() && !f.is_xml() {
+            return write!(f, "{key}", key = self.key);
+        }
+        write!(f, r#"{key}=""#, key = self.key)?;
+        match &self.val {
+            val if val.is_empty() && is_boolean_attribute(&self.key) => {
+                f.write_attr_value(&self.key)?
+            }
+            AttrValue::Str(s) => f.write_attr_value(s)?,
+            AttrValue::Fmt(v) => f.write_attr_display(v)?,
+            val @ AttrValue::Concat(_) => f.write_attr_display(val)?,
         }
-        write!(buf, r#"{key}="{val}""#, key = self.key, val = self.val)
+        f.write_char('"')
     }
 }
 
 /// Represents the content of an HTML tag
 ///
-/// This is a superset of the [`Html`] enum with the addition of the [`Str`] type (which is not a member of the top level Html enum).
-#[derive(Debug, Clone, PartialEq, Eq)]
+/// Either [`Html`] or plain text. Text can also be held as an [`Html::Text`] node, this variant keeps plain strings lightweight.
+///
+/// As for [`Fmt`], plain strings and formatted values are equal when they render to the same text.
+#[derive(Debug, Clone)]
 pub enum Content<'a> {
     /// Html content
     Html(Html<'a>),
-    /// Plain string
+    /// Plain string, which is escaped when rendered
     Str(Str<'a>),
+    /// A value formatted when rendered, e.g. a number, which is escaped like plain strings
+    Fmt(Fmt<'a>),
+}
+
+impl PartialEq for Content<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Content::Html(a), Content::Html(b)) => a == b,
+            (Content::Html(_), _) | (_, Content::Html(_)) => false,
+            (Content::Str(a), Content::Str(b)) => a == b,
+            (Content::Fmt(a), Content::Fmt(b)) => a == b,
+            (Content::Str(s), Content::Fmt(v)) | (Content::Fmt(v), Content::Str(s)) => {
+                *s == v.to_string()
+            }
+        }
+    }
 }
 
+impl Eq for Content<'_> {}
+
 impl<'a, T> From<T> for Content<'a>
 where
-    T: Into<Str<'a>>,
+    T: IntoStr<'a>,
 {
     fn from(value: T) -> Self {
-        Self::Str(value.into())
+        Self::Str(value.into_str())
+    }
+}
+
+impl<'a> From<Fmt<'a>> for Content<'a> {
+    fn from(value: Fmt<'a>) -> Self {
+        Self::Fmt(value)
     }
 }
 
@@ -288,30 +1162,107 @@ impl<'a> From<Comment<'a>> for Content<'a> {
     }
 }
 
+impl<'a> From<Doctype<'a>> for Content<'a> {
+    fn from(value: Doctype<'a>) -> Self {
+        Self::Html(Html::Doctype(value))
+    }
+}
+
+impl<'a> From<Raw<'a>> for Content<'a> {
+    fn from(value: Raw<'a>) -> Self {
+        Self::Html(Html::Raw(value))
+    }
+}
+
 impl<'a> From<Html<'a>> for Content<'a> {
     fn from(value: Html<'a>) -> Self {
         Self::Html(value)
     }
 }
 
+/// `None` becomes an empty sequence, which renders nothing
+impl<'a, T: Into<Content<'a>>> From<Option<T>> for Content<'a> {
+    fn from(value: Option<T>) -> Self {
+        value.map_or_else(|| Self::Html(Html::Html(vec![])), Into::into)
+    }
+}
+
+impl<'a, T: Into<Html<'a>>> FromIterator<T> for Content<'a> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::Html(iter.into_iter().collect())
+    }
+}
+
+/// Appends every item to the content of the tag with [`Tag::push_child`]
+impl<'a, T: Into<Content<'a>>> Extend<T> for Tag<'a> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        iter.into_iter().for_each(|c| self.push_child(c));
+    }
+}
+
 impl Default for Content<'_> {
     fn default() -> Self {
         Self::Str(Str::Borrowed(""))
     }
 }
 
+impl<'a> Content<'a> {
+    /// The nested node, if this content is not plain text
+    pub fn as_html(&self) -> Option<&Html<'a>> {
+        match self {
+            Content::Html(h) => Some(h),
+            Content::Str(_) | Content::Fmt(_) => None,
+        }
+    }
+
+    /// Mutable access to the nested node, if this content is not plain text
+    pub fn as_html_mut(&mut self) -> Option<&mut Html<'a>> {
+        match self {
+            Content::Html(h) => Some(h),
+            Content::Str(_) | Content::Fmt(_) => None,
+        }
+    }
+
+    /// The text, if this content is a plain string
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Content::Html(_) | Content::Fmt(_) => None,
+            Content::Str(s) => Some(s),
+        }
+    }
+
+    /// Whether this content can be placed on its own line when pretty-printing
+    fn is_block(&self) -> bool {
+        match self {
+            Content::Html(h) => h.is_block(),
+            Content::Str(_) => self.is_blank(),
+            Content::Fmt(_) => false,
+        }
+    }
+
+    /// Whether this content renders nothing but insignificant whitespace
+    fn is_blank(&self) -> bool {
+        match self {
+            Content::Html(h) => h.is_blank(),
+            Content::Str(s) => s.bytes().all(|b| b.is_ascii_whitespace()),
+            Content::Fmt(_) => false,
+        }
+    }
+}
+
 impl Render for Content<'_> {
-    fn render_to_buf(&self, buf: &mut String) -> fmt::Result {
+    fn render_fmt<W: Write + ?Sized>(&self, f: &mut Formatter<'_, W>) -> fmt::Result {
         match self {
-            Content::Html(h) => h.render_to_buf(buf),
-            Content::Str(s) => s.render_to_buf(buf),
+            Content::Html(h) => h.render_fmt(f),
+            Content::Str(s) => s.render_fmt(f),
+            Content::Fmt(v) => f.write_display(v),
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{Comment, Html, Render, Tag};
+    use crate::{AttrValue, Comment, Content, Doctype, Fmt, Html, Raw, Render, Renderer, Tag};
 
     #[test]
     fn tag() {
@@ -325,6 +1276,30 @@ mod tests {
         assert_eq!(tag.render(), Ok("<close>".into()));
     }
 
+    #[test]
+    fn void_elements() {
+        assert_eq!(
+            Tag::new("img").attr("src", "a.png").render(),
+            Ok(r#"<img src="a.png">"#.into())
+        );
+        assert_eq!(Tag::new("BR").render(), Ok("<BR>".into()));
+        assert_eq!(Tag::new("div").render(), Ok("<div></div>".into()));
+        assert_eq!(
+            Tag::new("br").content("lost").render(),
+            Err(std::fmt::Error)
+        );
+        assert_eq!(
+            Tag::new("x").self_closing().content("lost").render(),
+            Err(std::fmt::Error)
+        );
+        assert_eq!(
+            Tag::new("input")
+                .try_content("x")
+                .map_err(|e| e.name().to_owned()),
+            Err("input".into())
+        );
+    }
+
     #[test]
     fn tag_attributes() {
         let tag = Tag::new("tag").attr("key", "val").content("hello");
@@ -342,12 +1317,417 @@ mod tests {
         );
     }
 
+    #[test]
+    fn escaped_content() {
+        let tag = Tag::new("p").content("<b>Tom & Jerry</b>");
+        assert_eq!(
+            tag.render(),
+            Ok("<p>&lt;b&gt;Tom &amp; Jerry&lt;/b&gt;</p>".into())
+        );
+    }
+
+    #[test]
+    fn escaped_attr_value() {
+        let tag = Tag::new("a").attr("title", r#"say "hi" & <bye>"#);
+        assert_eq!(
+            tag.render(),
+            Ok(r#"<a title="say &quot;hi&quot; &amp; <bye>"></a>"#.into())
+        );
+    }
+
+    #[test]
+    fn raw_content() {
+        let tag = Tag::new("p")
+            .content(Raw::new("<b>trusted</b>"))
+            .content(" & escaped");
+        assert_eq!(
+            tag.render(),
+            Ok("<p><b>trusted</b> &amp; escaped</p>".into())
+        );
+    }
+
+    #[test]
+    fn render_to_sinks() {
+        struct Failing;
+        impl std::io::Write for Failing {
+            fn write(&mut self, _: &[u8]) -> std::io::Result<usize> {
+                Err(std::io::ErrorKind::BrokenPipe.into())
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let tag = Tag::new("p").attr("id", "x").content("a < b");
+        let expected = r#"<p id="x">a &lt; b</p>"#;
+        let mut bytes = Vec::new();
+        assert!(tag.render_to_io(&mut bytes).is_ok());
+        assert_eq!(bytes, expected.as_bytes());
+        let mut fmt_sink = String::new();
+        let dyn_sink: &mut dyn std::fmt::Write = &mut fmt_sink;
+        assert_eq!(tag.render_to_writer(dyn_sink), Ok(()));
+        assert_eq!(fmt_sink, expected);
+        assert_eq!(
+            tag.render_to_io(&mut Failing).map_err(|e| e.kind()),
+            Err(std::io::ErrorKind::BrokenPipe)
+        );
+        let list = Tag::new("ul").content(Tag::new("li").content(Tag::new("br").content("lost")));
+        assert_eq!(
+            list.render_to_io(&mut Vec::new())
+                .map_err(|e| (e.kind(), e.to_string())),
+            Err((
+                std::io::ErrorKind::InvalidInput,
+                "void element `br` cannot have content".into()
+            ))
+        );
+    }
+
+    #[test]
+    fn pretty_print() {
+        let doc: Html = vec![
+            Tag::new("head")
+                .content(Tag::new("title").content("Pretty"))
+                .into(),
+            Tag::new("body")
+                .content(Comment::new("nav"))
+                .content(
+                    Tag::new("div")
+                        .content(
+                            Tag::new("p")
+                                .content("text ")
+                                .content(Tag::new("b").content("bold")),
+                        )
+                        .content(Tag::new("pre").content(Tag::new("p").content(" keep\n  as is")))
+                        .content(
+                            Tag::new("div").content(Tag::new("span").content(Tag::new("div"))),
+                        ),
+                )
+                .into(),
+        ]
+        .into();
+        assert_eq!(
+            Renderer::new().indent(2).render(&doc),
+            Ok("<head>\n  <title>Pretty</title>\n</head>\n<body>\n  <!-- nav -->\n  <div>\n    <p>text <b>bold</b></p>\n    <pre><p> keep\n  as is</p></pre>\n    <div><span><div></div></span></div>\n  </div>\n</body>".into())
+        );
+        assert_eq!(
+            Renderer::new()
+                .indent(2)
+                .render(&Tag::new("div").content("  ").content(Tag::new("p"))),
+            Ok("<div>\n  <p></p>\n</div>".into())
+        );
+        let seq: Html = vec![Comment::new("1").into(), Comment::new("2").into()].into();
+        let divs: Content = (1..=2).map(|i| Tag::new("div").content(i)).collect();
+        let p = Tag::new("p")
+            .content("a")
+            .content(seq)
+            .content("b")
+            .content(divs);
+        assert_eq!(
+            Renderer::new().indent(2).render(&p),
+            Ok("<p>a<!-- 1 --><!-- 2 -->b<div>1</div><div>2</div></p>".into())
+        );
+        assert_eq!(
+            Renderer::new()
+                .indent(2)
+                .render(&Tag::new("div").content(p.clone()).content(Tag::new("hr"))),
+            Ok(
+                "<div>\n  <p>a<!-- 1 --><!-- 2 -->b<div>1</div><div>2</div></p>\n  <hr>\n</div>"
+                    .into()
+            )
+        );
+    }
+
+    #[test]
+    fn xml_output() {
+        let xml = Renderer::new().xml();
+        let tag = Tag::new("form")
+            .content(Tag::new("img").attr("alt", "").attr("title", "a<b"))
+            .content(Tag::new("option").attr("selected", "").content("x"))
+            .content(Tag::new("script").attr("async", "").attr("src", "a.js"));
+        assert_eq!(
+            xml.render(&tag),
+            Ok(r#"<form><img alt="" title="a&lt;b" /><option selected="selected">x</option><script async="async" src="a.js" /></form>"#.into())
+        );
+        assert_eq!(
+            xml.indent(2).render(
+                &Tag::new("ul")
+                    .content(Tag::new("li"))
+                    .content(Tag::new("li"))
+            ),
+            Ok("<ul>\n  <li />\n  <li />\n</ul>".into())
+        );
+        assert_eq!(
+            tag.render(),
+            Ok(r#"<form><img alt title="a<b"><option selected>x</option><script async src="a.js"></script></form>"#.into())
+        );
+    }
+
+    #[test]
+    fn accessors() {
+        let tag = Tag::new("a")
+            .attr("href", "/")
+            .attr("download", "")
+            .content("home")
+            .content(Comment::new("c"));
+        assert_eq!(tag.name(), "a");
+        assert_eq!(tag.get_attr("href").as_deref(), Some("/"));
+        assert_eq!(tag.get_attr("download").as_deref(), Some(""));
+        assert_eq!(tag.get_attr("title"), None);
+        let attrs: Vec<_> = tag
+            .attributes()
+            .iter()
+            .map(|a| (a.key(), a.value().as_str()))
+            .collect();
+        assert_eq!(attrs, [("href", Some("/")), ("download", Some(""))]);
+        assert_eq!(tag.children()[0].as_str(), Some("home"));
+        assert_eq!(
+            tag.children()[1].as_html(),
+            Some(&Html::Comment(Comment::new("c")))
+        );
+        assert!(!tag.is_self_closing());
+    }
+
+    #[test]
+    fn traversal() {
+        let tree: Html = vec![
+            Tag::new("a")
+                .content(Tag::new("b").content(Tag::new("d")))
+                .content(Tag::new("c"))
+                .into(),
+            Html::Html(vec![Tag::new("e").content(Tag::new("f")).into()]),
+        ]
+        .into();
+        let names = |it: &mut dyn Iterator<Item = &Html>| -> Vec<String> {
+            it.filter_map(Html::as_tag)
+                .map(|t| t.name().to_owned())
+                .collect()
+        };
+        assert_eq!(names(&mut tree.dfs()), ["a", "b", "d", "c", "e", "f"]);
+        assert_eq!(names(&mut tree.bfs()), ["a", "e", "b", "c", "f", "d"]);
+        let a = tree.tags().next();
+        assert_eq!(
+            a.map(|a| names(&mut a.descendants())),
+            Some(vec!["b".into(), "d".into(), "c".into()])
+        );
+    }
+
+    #[test]
+    fn repeated_attrs() {
+        let tag = Tag::new("a")
+            .attr("class", "btn")
+            .attr("href", "/old")
+            .attr("class", "btn-primary btn")
+            .attr("href", "/new");
+        assert_eq!(
+            tag.render(),
+            Ok(r#"<a class="btn btn-primary" href="/new"></a>"#.into())
+        );
+    }
+
+    #[test]
+    fn invalid_attr_keys() {
+        use std::collections::BTreeMap;
+
+        let hostile = BTreeMap::from([
+            (r#""><script>alert(1)</script>"#, "x"),
+            ("x onmouseover=alert(1) y", "x"),
+            ("a/b", "x"),
+            ("", "x"),
+            ("tab\t", "x"),
+            ("data-ok", "y"),
+        ]);
+        let mut tag = Tag::new("button").attrs(hostile).content("Go");
+        tag.set_attr("it's", "x");
+        assert_eq!(
+            tag.render(),
+            Ok(r#"<button data-ok="y">Go</button>"#.into())
+        );
+    }
+
+    #[test]
+    fn optional_attrs() {
+        let none: Option<&str> = None;
+        let tag = Tag::new("option")
+            .attr("value", Some(3))
+            .attr("label", none)
+            .attr("class", none)
+            .bool_attr("selected", true)
+            .bool_attr("disabled", false);
+        assert_eq!(
+            tag.render(),
+            Ok(r#"<option value="3" selected></option>"#.into())
+        );
+        assert_eq!(
+            Renderer::new().xml().render(&tag),
+            Ok(r#"<option value="3" selected="selected" />"#.into())
+        );
+    }
+
+    #[test]
+    fn attr_collisions() {
+        let none: Option<&str> = None;
+        let tag = Tag::new("a")
+            .attr("title", "x")
+            .attr("href", "/")
+            .attrs([("title", none), ("href", Some("/home"))])
+            .attr("class", "a")
+            .attr("class", none);
+        assert_eq!(tag.render(), Ok(r#"<a href="/home" class="a"></a>"#.into()));
+        let tag = Tag::new("input")
+            .attr("disabled", "")
+            .attrs([("disabled", false), ("required", true)])
+            .bool_attr("required", false)
+            .attr("checked", true)
+            .attr("checked", Some(true));
+        assert_eq!(tag.render(), Ok("<input checked>".into()));
+    }
+
+    #[test]
+    fn attr_mutation() {
+        let mut tag = Tag::new("input").attr("type", "text").attr("class", "a b");
+        tag.set_attr("type", "email");
+        tag.set_attr("required", "");
+        assert!(tag.has_attr("required"));
+        assert_eq!(tag.remove_attr("required"), Some("".into()));
+        assert_eq!(tag.remove_attr("required"), None);
+        tag.add_class("c a");
+        assert!(tag.has_class("c"));
+        tag.remove_class("a");
+        assert!(!tag.has_class("a"));
+        assert_eq!(
+            tag.render(),
+            Ok(r#"<input type="email" class="b c">"#.into())
+        );
+        tag.remove_class("b");
+        tag.remove_class("c");
+        assert!(!tag.has_attr("class"));
+    }
+
+    #[test]
+    fn child_mutation() {
+        let mut tag = Tag::new("ul").content(Tag::new("li").content("b"));
+        tag.push_child(Tag::new("li").content("c"));
+        tag.insert_child(0, Tag::new("li").content("a"));
+        assert_eq!(tag.remove_child(5), None);
+        assert_eq!(
+            tag.remove_child(1),
+            Some(Tag::new("li").content("b").into())
+        );
+        tag.children_mut().push(Comment::new("end").into());
+        assert_eq!(
+            tag.render(),
+            Ok("<ul><li>a</li><li>c</li><!-- end --></ul>".into())
+        );
+    }
+
+    #[test]
+    fn html_mutation() {
+        let mut html = Html::Tag(Tag::new("main").content(Tag::new("p").content("x")));
+        html.push(Tag::new("footer"));
+        if let Some(p) = html.find_tag_mut(|t| t.name() == "p") {
+            p.set_attr("id", "first");
+        }
+        assert_eq!(
+            html.render(),
+            Ok(r#"<main><p id="first">x</p></main><footer></footer>"#.into())
+        );
+    }
+
     #[test]
     fn comment() {
         let c = Comment::new("a comment");
         assert_eq!(c.render(), Ok("<!-- a comment -->".into()));
     }
 
+    #[test]
+    fn formatted_values() {
+        struct Money(u32);
+        impl std::fmt::Display for Money {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "<{}.{:02} €>", self.0 / 100, self.0 % 100)
+            }
+        }
+        let (price, tip) = (Money(1250), Money(5));
+        let tag = Tag::new("td")
+            .attr("colspan", 2u8)
+            .attr("data-price", Fmt::display(&price))
+            .attr("class", 7)
+            .content(-3)
+            .content(' ')
+            .content(0.1f32)
+            .content(true)
+            .content(Fmt::display(&tip));
+        assert_eq!(
+            tag.render(),
+            Ok(
+                r#"<td colspan="2" data-price="<12.50 €>" class="7">-3 0.1true&lt;0.05 €&gt;</td>"#
+                    .into()
+            )
+        );
+        assert_eq!(
+            Renderer::new()
+                .xml()
+                .render(&tag)
+                .map(|s| s.contains(r#"data-price="&lt;12.50"#)),
+            Ok(true)
+        );
+        assert_eq!(tag.get_attr("colspan").as_deref(), Some("2"));
+        assert_eq!(tag.select("*").map(|t| t.len()), Ok(0));
+        assert_eq!(Fmt::from(2u8), Fmt::from(2i64));
+        assert_eq!(AttrValue::from("3"), AttrValue::from(3));
+        assert_eq!(Content::from("1"), Content::from(1.0));
+        assert_ne!(Content::from("1"), Content::from(Raw::new("1")));
+
+        let mut tag = tag;
+        tag.add_class("a");
+        tag.set_attr("colspan", 3);
+        assert_eq!(tag.get_attr("class").as_deref(), Some("7 a"));
+        assert_eq!(tag.remove_attr("colspan"), Some(3.into()));
+    }
+
+    #[test]
+    fn option_and_iterators() {
+        let none: Option<Tag> = None;
+        assert_eq!(Html::from(none).render(), Ok("".into()));
+        assert_eq!(Html::from(Some(Tag::new("b"))), Html::Tag(Tag::new("b")));
+        let list: Html = ["a", "b"].into_iter().collect();
+        assert_eq!(list.render(), Ok("ab".into()));
+        let mut html = Html::from(Tag::new("hr"));
+        html.extend([Tag::new("br"), Tag::new("img")]);
+        assert_eq!(html.render(), Ok("<hr><br><img>".into()));
+
+        let img = Tag::new("img").content(None::<&str>);
+        assert!(img.children().is_empty());
+        assert_eq!(img.render(), Ok("<img>".into()));
+        let mut ul = Tag::new("ul").content(None::<&str>);
+        ul.extend((1..=2).map(|i| Tag::new("li").content(i)));
+        let items: Content = (3..=4).map(|i| Tag::new("li").content(i)).collect();
+        assert_eq!(
+            ul.content(items).render(),
+            Ok("<ul><li>1</li><li>2</li><li>3</li><li>4</li></ul>".into())
+        );
+    }
+
+    #[test]
+    fn text_nodes() {
+        let name = String::from("<you>");
+        let html: Html = vec![
+            Html::from("Hello, "),
+            Tag::new("b").content(name.clone()).into(),
+            Html::from(name),
+        ]
+        .into();
+        assert_eq!(
+            html.render(),
+            Ok("Hello, <b>&lt;you&gt;</b>&lt;you&gt;".into())
+        );
+        assert_eq!(html.dfs().filter_map(Html::as_str).count(), 2);
+        assert_eq!(
+            Tag::new("p").content(Html::from("a & b")).render(),
+            Ok("<p>a &amp; b</p>".into())
+        );
+    }
+
     #[test]
     fn html_doc() {
         let inner: Html = vec![
@@ -368,4 +1748,26 @@ mod tests {
             Ok(r#"<!DOCTYPE html><head><title>Html Doc</title></head><body><!-- a comment --><p>hello world</p></body>"#.into())
         );
     }
+
+    #[test]
+    fn doctype_is_not_a_tag() {
+        let doc: Html = vec![
+            Doctype::new("html").into(),
+            Tag::new("head")
+                .content(Tag::new("title").content("Html Doc"))
+                .into(),
+            Tag::new("body")
+                .content(Tag::new("p").content("hello world"))
+                .into(),
+        ]
+        .into();
+        assert_eq!(
+            doc.render(),
+            Ok(r#"<!DOCTYPE html><head><title>Html Doc</title></head><body><p>hello world</p></body>"#.into())
+        );
+        assert_eq!(doc.dfs().count(), 5);
+        let names: Vec<_> = doc.tags().map(Tag::name).collect();
+        assert_eq!(names, ["head", "title", "body", "p"]);
+        assert_eq!(doc.select("*").map(|t| t.len()), Ok(4));
+    }
 }
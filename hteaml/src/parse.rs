@@ -0,0 +1,633 @@
+//! A lenient parser turning HTML strings into [`Html`] trees
+use std::borrow::Cow;
+use std::error::Error;
+use std::fmt;
+
+use crate::render::is_void_element;
+use crate::{Attr, AttrValue, Comment, Content, Doctype, Html, Raw, Str, Tag};
+
+mod entities;
+
+impl<'a> Html<'a> {
+    /// Parse an HTML string into an [`Html`] tree
+    ///
+    /// The parser is lenient in the way browsers are, without implementing the full HTML5 tree construction algorithm:
+    /// - void elements (e.g. `<br>`, `<img>`) never take content, stray end tags are ignored and unclosed elements are closed
+    ///   at the end of their parent or of the input
+    /// - common implied end tags are handled, e.g. a `<li>` closes the previous `<li>` and a `<div>` closes an open `<p>`
+    /// - `<x/>` on a non-void element produces an empty element (rendered as `<x></x>`)
+    /// - attributes may be double quoted, single quoted, unquoted or boolean (without a value), the first of duplicate attributes wins
+    /// - numeric and HTML5 named character references ending with `;` are decoded in text and attribute values, so the
+    ///   tree holds plain text that is escaped again when rendered; an `&` that doesn't start one is kept as text
+    /// - the content of `<script>` and `<style>` is kept as [`Raw`] markup, `<textarea>` and `<title>` only hold text
+    /// - comments and the doctype are preserved, other declarations and processing instructions are dropped
+    ///
+    /// Text outside of any element becomes an [`Html::Text`] node, unless it is only whitespace.
+    ///
+    /// Strings in the tree borrow from `input` unless decoding entities required allocating.
+    /// A single top-level node is returned as is, several are returned as an [`Html::Html`] sequence.
+    ///
+    /// ## Example
+    /// ```
+    /// use hteaml::{Html, Render};
+    /// let html = Html::parse(r#"<ul class=list><li>Tom &amp; Jerry<li><input disabled></ul>"#)?;
+    /// assert_eq!(
+    ///     html.render(),
+    ///     Ok(r#"<ul class="list"><li>Tom &amp; Jerry</li><li><input disabled></li></ul>"#.into())
+    /// );
+    /// # Ok::<(), hteaml::ParseError>(())
+    /// ```
+    pub fn parse(input: &'a str) -> Result<Self, ParseError> {
+        Parser {
+            input,
+            pos: 0,
+            open: vec![],
+            nodes: vec![],
+        }
+        .parse()
+    }
+}
+
+/// The error returned by [`Html::parse`] for input that cannot be parsed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    message: &'static str,
+    line: usize,
+    column: usize,
+}
+
+impl ParseError {
+    fn new(input: &str, pos: usize, message: &'static str) -> Self {
+        let before = &input[..pos];
+        let line = before.matches('\n').count() + 1;
+        let column = before.rsplit('\n').next().map_or(0, |l| l.chars().count()) + 1;
+        Self {
+            message,
+            line,
+            column,
+        }
+    }
+
+    /// Description of what went wrong
+    pub fn message(&self) -> &str {
+        self.message
+    }
+
+    /// The line (starting at 1) at which the error occurred
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// The column (starting at 1, counted in characters) at which the error occurred
+    pub fn column(&self) -> usize {
+        self.column
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at {}:{}", self.message, self.line, self.column)
+    }
+}
+
+impl Error for ParseError {}
+
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+    /// Elements that have been opened but not closed yet, innermost last
+    open: Vec<Tag<'a>>,
+    /// Finished top-level nodes
+    nodes: Vec<Html<'a>>,
+}
+
+impl<'a> Parser<'a> {
+    fn parse(mut self) -> Result<Html<'a>, ParseError> {
+        while self.pos < self.input.len() {
+            let rest = self.rest();
+            if rest.starts_with("<!--") {
+                self.comment()?;
+            } else if rest.starts_with("</") {
+                self.end_tag()?;
+            } else if rest.starts_with("<!") || rest.starts_with("<?") {
+                self.declaration()?;
+            } else if rest.starts_with('<')
+                && rest[1..].starts_with(|c: char| c.is_ascii_alphabetic())
+            {
+                self.start_tag()?;
+            } else {
+                self.text();
+            }
+        }
+        while !self.open.is_empty() {
+            self.close_innermost();
+        }
+        Ok(match self.nodes.len() {
+            1 => self.nodes.remove(0),
+            _ => Html::Html(self.nodes),
+        })
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    fn error(&self, message: &'static str) -> ParseError {
+        ParseError::new(self.input, self.pos, message)
+    }
+
+    /// Append a finished node to the innermost open element, or to the top level
+    fn push(&mut self, node: Content<'a>) {
+        match (self.open.last_mut(), node) {
+            (Some(parent), node) => parent.content.push(node),
+            (None, Content::Html(html)) => self.nodes.push(html),
+            (None, Content::Str(_) | Content::Fmt(_)) => {}
+        }
+    }
+
+    fn close_innermost(&mut self) {
+        if let Some(tag) = self.open.pop() {
+            self.push(Content::Html(Html::Tag(tag)));
+        }
+    }
+
+    fn text(&mut self) {
+        let rest = self.rest();
+        // skip the leading char so a lone '<' that doesn't start markup is taken as text
+        let len = rest
+            .char_indices()
+            .skip(1)
+            .find(|&(_, c)| c == '<')
+            .map_or(rest.len(), |(i, _)| i);
+        let text = &rest[..len];
+        self.pos += len;
+        if self.open.is_empty() {
+            if !text.trim().is_empty() {
+                self.nodes.push(Html::Text(decode_entities(text)));
+            }
+            return;
+        }
+        self.push(Content::Str(decode_entities(text)));
+    }
+
+    fn comment(&mut self) -> Result<(), ParseError> {
+        let body = &self.rest()["<!--".len()..];
+        let end = body
+            .find("-->")
+            .ok_or_else(|| self.error("unterminated comment"))?;
+        self.pos += "<!--".len() + end + "-->".len();
+        self.push(Content::Html(Html::Comment(Comment::new(
+            body[..end].trim(),
+        ))));
+        Ok(())
+    }
+
+    fn declaration(&mut self) -> Result<(), ParseError> {
+        let rest = self.rest();
+        let end = rest
+            .find('>')
+            .ok_or_else(|| self.error("unterminated declaration"))?;
+        self.pos += end + 1;
+        let decl = &rest[2..end];
+        let keyword = "doctype";
+        if decl
+            .get(..keyword.len())
+            .is_some_and(|d| d.eq_ignore_ascii_case(keyword))
+        {
+            let doctype = doctype(&decl[keyword.len()..]);
+            self.push(Content::Html(Html::Doctype(doctype)));
+        }
+        Ok(())
+    }
+
+    fn end_tag(&mut self) -> Result<(), ParseError> {
+        let rest = self.rest();
+        let end = rest
+            .find('>')
+            .ok_or_else(|| self.error("unterminated end tag"))?;
+        self.pos += end + 1;
+        let name = rest[2..end].trim();
+        if let Some(depth) = self
+            .open
+            .iter()
+            .rposition(|t| t.name.eq_ignore_ascii_case(name))
+        {
+            while self.open.len() > depth {
+                self.close_innermost();
+            }
+        }
+        Ok(())
+    }
+
+    fn start_tag(&mut self) -> Result<(), ParseError> {
+        self.pos += 1;
+        let name = self.take_while(|c| !c.is_ascii_whitespace() && c != '/' && c != '>');
+        let mut tag = Tag::new(name);
+        let self_closing = loop {
+            self.skip_whitespace();
+            let rest = self.rest();
+            if rest.starts_with("/>") {
+                self.pos += 2;
+                break true;
+            } else if rest.starts_with('>') {
+                self.pos += 1;
+                break false;
+            } else if rest.starts_with('/') {
+                self.pos += 1;
+            } else if rest.is_empty() {
+                return Err(self.error("unterminated start tag"));
+            } else {
+                let attr = self.attribute()?;
+                if !tag.attributes.iter().any(|a| a.key == attr.key) {
+                    tag.attributes.push(attr);
+                }
+            }
+        };
+
+        while self
+            .open
+            .last()
+            .is_some_and(|open| closes_implicitly(&tag.name, &open.name))
+        {
+            self.close_innermost();
+        }
+
+        if self_closing || is_void_element(&tag.name) {
+            self.push(Content::Html(Html::Tag(tag)));
+        } else if let Some(raw) = ["script", "style", "textarea", "title"]
+            .iter()
+            .find(|raw| raw.eq_ignore_ascii_case(&tag.name))
+        {
+            let text = self.raw_text(&tag.name);
+            if matches!(*raw, "script" | "style") && !text.is_empty() {
+                tag.content.push(Content::Html(Html::Raw(Raw::new(text))));
+            } else if !text.is_empty() {
+                tag.content.push(Content::Str(decode_entities(text)));
+            }
+            self.push(Content::Html(Html::Tag(tag)));
+        } else {
+            self.open.push(tag);
+        }
+        Ok(())
+    }
+
+    fn attribute(&mut self) -> Result<Attr<'a>, ParseError> {
+        let start = self.pos;
+        if self.rest().starts_with('=') {
+            // an `=` without a name starts the name, like browsers do
+            self.pos += 1;
+        }
+        self.take_while(|c| !c.is_ascii_whitespace() && !matches!(c, '=' | '>' | '/'));
+        let key = &self.input[start..self.pos];
+        self.skip_whitespace();
+        if !self.rest().starts_with('=') {
+            return Ok(Attr {
+                key: Cow::Borrowed(key),
+                val: AttrValue::Str(Cow::Borrowed("")),
+            });
+        }
+        self.pos += 1;
+        self.skip_whitespace();
+        let val = match self.rest().chars().next() {
+            Some(quote @ ('"' | '\'')) => {
+                self.pos += 1;
+                let val = self.take_while(|c| c != quote);
+                if self.rest().is_empty() {
+                    return Err(self.error("unterminated attribute value"));
+                }
+                self.pos += 1;
+                val
+            }
+            _ => self.take_while(|c| !c.is_ascii_whitespace() && c != '>'),
+        };
+        Ok(Attr {
+            key: Cow::Borrowed(key),
+            val: AttrValue::Str(decode_entities(val)),
+        })
+    }
+
+    /// Take everything up to the end tag of a raw text element, leaving the end tag to be parsed normally
+    fn raw_text(&mut self, name: &str) -> &'a str {
+        let rest = self.rest();
+        let mut from = 0;
+        let end = loop {
+            match rest[from..].find("</") {
+                Some(i) => {
+                    let candidate = &rest[from + i + 2..];
+                    if candidate.len() >= name.len()
+                        && candidate.is_char_boundary(name.len())
+                        && candidate[..name.len()].eq_ignore_ascii_case(name)
+                    {
+                        break from + i;
+                    }
+                    from += i + 2;
+                }
+                None => break rest.len(),
+            }
+        };
+        self.pos += end;
+        let text = &rest[..end];
+        if self.rest().is_empty() {
+            return text;
+        }
+        let close = self.rest().find('>').map_or(self.rest().len(), |i| i + 1);
+        self.pos += close;
+        text
+    }
+
+    fn take_while(&mut self, f: impl Fn(char) -> bool) -> &'a str {
+        let rest = self.rest();
+        let len = rest.find(|c| !f(c)).unwrap_or(rest.len());
+        self.pos += len;
+        &rest[..len]
+    }
+
+    fn skip_whitespace(&mut self) {
+        self.take_while(|c| c.is_ascii_whitespace());
+    }
+}
+
+/// Open elements (first) whose end is implied by the start of one of the elements (second)
+const IMPLIED_END_TAGS: &[(&[&str], &[&str])] = &[
+    (
+        &["p"],
+        &[
+            "address",
+            "article",
+            "aside",
+            "blockquote",
+            "div",
+            "dl",
+            "fieldset",
+            "footer",
+            "form",
+            "h1",
+            "h2",
+            "h3",
+            "h4",
+            "h5",
+            "h6",
+            "header",
+            "hr",
+            "main",
+            "nav",
+            "ol",
+            "p",
+            "pre",
+            "section",
+            "table",
+            "ul",
+        ],
+    ),
+    (&["li"], &["li"]),
+    (&["dt", "dd"], &["dt", "dd"]),
+    (&["option"], &["option", "optgroup"]),
+    (&["td", "th"], &["td", "th", "tr"]),
+    (&["tr"], &["tr"]),
+    (&["thead", "tbody"], &["tbody", "tfoot"]),
+];
+
+/// Parse the part of a doctype after the `doctype` keyword, e.g. ` html PUBLIC "-//W3C//DTD XHTML 1.0 Strict//EN" "..."`
+///
+/// Identifiers that are missing their quotes are dropped.
+fn doctype(decl: &str) -> Doctype<'_> {
+    let decl = decl.trim_start();
+    let end = decl.find(char::is_whitespace).unwrap_or(decl.len());
+    let doctype = Doctype::new(&decl[..end]);
+    let mut rest = decl[end..].trim_start();
+    let mut keyword = |k: &str| {
+        let found = rest
+            .get(..k.len())
+            .is_some_and(|r| r.eq_ignore_ascii_case(k));
+        if found {
+            rest = rest[k.len()..].trim_start();
+        }
+        found
+    };
+    if keyword("public") {
+        match quoted(&mut rest) {
+            Some(public) => match quoted(&mut rest) {
+                Some(system) => doctype.public_id(public).system_id(system),
+                None => doctype.public_id(public),
+            },
+            None => doctype,
+        }
+    } else if keyword("system") {
+        match quoted(&mut rest) {
+            Some(system) => doctype.system_id(system),
+            None => doctype,
+        }
+    } else {
+        doctype
+    }
+}
+
+/// Take a single or double quoted string from the start of `s`
+fn quoted<'a>(s: &mut &'a str) -> Option<&'a str> {
+    let quote = s.chars().next().filter(|&c| c == '"' || c == '\'')?;
+    let end = s[1..].find(quote)?;
+    let value = &s[1..end + 1];
+    *s = s[end + 2..].trim_start();
+    Some(value)
+}
+
+/// Whether opening an element `name` implies the end of the open element `open`
+fn closes_implicitly(name: &str, open: &str) -> bool {
+    let any_is = |names: &[&str], n: &str| names.iter().any(|e| e.eq_ignore_ascii_case(n));
+    IMPLIED_END_TAGS
+        .iter()
+        .find(|(opens, _)| any_is(opens, open))
+        .is_some_and(|(_, closers)| any_is(closers, name))
+}
+
+/// Decode character references, only allocating if there are any
+fn decode_entities(text: &str) -> Str<'_> {
+    if !text.contains('&') {
+        return Cow::Borrowed(text);
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        match rest
+            .find(';')
+            .and_then(|semi| Some((entity(&rest[1..semi])?, semi)))
+        {
+            Some((text, semi)) => {
+                out.push_str(&text);
+                rest = &rest[semi + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    Cow::Owned(out)
+}
+
+/// The text of a numeric or a named character reference (without the `&` and `;`)
+///
+/// Every HTML5 named reference is known, see [`entities::ENTITIES`].
+fn entity(name: &str) -> Option<Str<'static>> {
+    if let Some(num) = name.strip_prefix('#') {
+        let code = match num.strip_prefix(['x', 'X']) {
+            Some(hex) => u32::from_str_radix(hex, 16),
+            None => num.parse(),
+        };
+        return code
+            .ok()
+            .and_then(char::from_u32)
+            .map(|c| Cow::Owned(c.into()));
+    }
+    entities::ENTITIES
+        .binary_search_by(|(entity, _)| (*entity).cmp(name))
+        .ok()
+        .map(|i| Cow::Borrowed(entities::ENTITIES[i].1))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Comment, Doctype, Html, ParseError, Raw, Render, Tag};
+
+    #[test]
+    fn elements_and_attributes() -> Result<(), ParseError> {
+        let html = Html::parse(r#"<div id="a" class='b c' data-x=1 hidden><p>hi</p></div>"#)?;
+        let expected = Tag::new("div")
+            .attr("id", "a")
+            .attr("class", "b c")
+            .attr("data-x", "1")
+            .attr("hidden", "")
+            .content(Tag::new("p").content("hi"));
+        assert_eq!(html, Html::Tag(expected));
+        Ok(())
+    }
+
+    #[test]
+    fn document() -> Result<(), ParseError> {
+        let html = Html::parse(
+            "<!DOCTYPE html>\n<html>\n<head><title>A &lt; B</title><!-- note --></head>\n<body><br><img src=x.png /></body></html>\n",
+        )?;
+        let expected = Html::Html(vec![
+            Doctype::new("html").into(),
+            Tag::new("html")
+                .content("\n")
+                .content(
+                    Tag::new("head")
+                        .content(Tag::new("title").content("A < B"))
+                        .content(Comment::new("note")),
+                )
+                .content("\n")
+                .content(
+                    Tag::new("body")
+                        .content(Tag::new("br"))
+                        .content(Tag::new("img").attr("src", "x.png")),
+                )
+                .into(),
+        ]);
+        assert_eq!(html, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn entities() -> Result<(), ParseError> {
+        let html = Html::parse(
+            "<p title=\"&quot;q&quot; &amp;\">&copy; &#169; &#xA9; &unknown; & done</p>",
+        )?;
+        let expected = Tag::new("p")
+            .attr("title", "\"q\" &")
+            .content("© © © &unknown; & done");
+        assert_eq!(html, Html::Tag(expected));
+        Ok(())
+    }
+
+    #[test]
+    fn implied_end_tags() -> Result<(), ParseError> {
+        let html = Html::parse("<ul><li>one<li>two</ul><p>a<div>b</div></span>")?;
+        assert_eq!(
+            html.render(),
+            Ok("<ul><li>one</li><li>two</li></ul><p>a</p><div>b</div>".into())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn raw_text_elements() -> Result<(), ParseError> {
+        let html =
+            Html::parse("<script>if (a < b && c) {}</script><textarea>&lt;b&gt;</textarea>")?;
+        let expected = Html::Html(vec![
+            Tag::new("script")
+                .content(Raw::new("if (a < b && c) {}"))
+                .into(),
+            Tag::new("textarea").content("<b>").into(),
+        ]);
+        assert_eq!(html, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn round_trip() -> Result<(), ParseError> {
+        let src = r#"<nav class="top"><a href="/?a=1&amp;b=2">Home &amp; away</a><!-- x --><input value="&quot;"></nav>"#;
+        assert_eq!(Html::parse(src)?.render(), Ok(src.into()));
+        assert_eq!(Html::parse("<a =x>")?.render(), Ok("<a =x></a>".into()));
+        let legacy =
+            "<p title=\"&eacute;t&eacute;\">caf&eacute; &hearts; &NotNestedLessLess; &bogus;</p>";
+        assert_eq!(
+            Html::parse(legacy)?.render(),
+            Ok("<p title=\"été\">café ♥ ⪡̸ &amp;bogus;</p>".into())
+        );
+        assert_eq!(Html::parse("<!doctype>")?.render(), Ok("<!DOCTYPE>".into()));
+        Ok(())
+    }
+
+    #[test]
+    fn top_level_text() -> Result<(), ParseError> {
+        let html = Html::parse("Hello, <b>you</b> &amp; me\n")?;
+        let expected = Html::Html(vec![
+            Html::from("Hello, "),
+            Tag::new("b").content("you").into(),
+            Html::from(" & me\n"),
+        ]);
+        assert_eq!(html, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn doctypes() -> Result<(), ParseError> {
+        assert_eq!(Html::parse("<!doctype HTML>")?, Doctype::new("HTML").into());
+        let xhtml = r#"<!DOCTYPE html PUBLIC "-//W3C//DTD XHTML 1.1//EN" 'http://www.w3.org/TR/xhtml11/DTD/xhtml11.dtd'>"#;
+        assert_eq!(
+            Html::parse(xhtml)?,
+            Doctype::new("html")
+                .public_id("-//W3C//DTD XHTML 1.1//EN")
+                .system_id("http://www.w3.org/TR/xhtml11/DTD/xhtml11.dtd")
+                .into()
+        );
+        assert_eq!(
+            Html::parse(r#"<!DOCTYPE svg SYSTEM "svg.dtd">"#)?,
+            Doctype::new("svg").system_id("svg.dtd").into()
+        );
+        assert_eq!(
+            Html::parse("<!DOCTYPE html PUBLIC>")?,
+            Doctype::new("html").into()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn errors() {
+        let err = Html::parse("<div>\n  <!-- open").map(|_| ());
+        assert_eq!(
+            err.as_ref().map_err(|e| (e.line(), e.column())),
+            Err((2, 3))
+        );
+        assert_eq!(
+            err.map_err(|e| e.to_string()),
+            Err("unterminated comment at 2:3".into())
+        );
+        assert!(Html::parse("<a href=\"x").is_err());
+        assert!(Html::parse("<a href=x").is_err());
+    }
+}
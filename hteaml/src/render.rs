@@ -0,0 +1,395 @@
+//! Output settings and the formatter that carries them through a [`Render`] pass
+use std::fmt::{self, Write};
+use std::io;
+
+use crate::{escape, Render, VoidElementError};
+
+/// Settings for rendering HTML, such as pretty-printing
+///
+/// The methods on [`Render`] use the default settings, which write compact HTML on a single line.
+///
+/// ## Pretty-printing
+/// With [`Renderer::indent`] block-level elements (e.g. `div`, `p`, `ul`, `li`) whose children are all block-level are
+/// spread over multiple indented lines. Whitespace is only ever inserted where the browser ignores it: elements that
+/// contain text or inline elements (e.g. `span`, `a`, `b`) are kept on one line, and whitespace-sensitive elements such as
+/// `<pre>` and `<textarea>` are written exactly as they are. Turning pretty-printing on never changes how the page renders.
+///
+/// ## XHTML
+/// With [`Renderer::xml`] the output is well-formed XML, as required by XHTML consumers such as EPUB readers and feed
+/// parsers: elements without content are written as `<br />`, and every attribute gets a quoted value, with boolean
+/// attributes expanded to `checked="checked"`.
+///
+/// ## Example
+/// ```
+/// use hteaml::{hteaml, Renderer};
+/// let html = hteaml!((ul (li = "one") (li (a href:"/" = "two"))));
+/// assert_eq!(
+///     Renderer::new().indent(2).render(&html),
+///     Ok("<ul>\n  <li>one</li>\n  <li><a href=\"/\">two</a></li>\n</ul>".into())
+/// );
+///
+/// let html = hteaml!((p (input type:checkbox checked) (br)));
+/// assert_eq!(
+///     Renderer::new().xml().render(&html),
+///     Ok("<p><input type=\"checkbox\" checked=\"checked\" /><br /></p>".into())
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Renderer {
+    indent: Option<usize>,
+    xml: bool,
+}
+
+impl Renderer {
+    /// Create a renderer with the default settings, which writes compact HTML
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Pretty-print the output, indenting nested block-level elements by `width` spaces per level
+    pub fn indent(mut self, width: usize) -> Self {
+        self.indent = Some(width);
+        self
+    }
+
+    /// Write XML compatible output (XHTML) instead of HTML
+    pub fn xml(mut self) -> Self {
+        self.xml = true;
+        self
+    }
+
+    /// Render `html` to a `String`
+    pub fn render<T: Render + ?Sized>(&self, html: &T) -> Result<String, fmt::Error> {
+        let mut buf = String::new();
+        self.render_to_writer(html, &mut buf)?;
+        Ok(buf)
+    }
+
+    /// Render `html` by writing to the given [`fmt::Write`] sink
+    pub fn render_to_writer<T, W>(&self, html: &T, w: &mut W) -> fmt::Result
+    where
+        T: Render + ?Sized,
+        W: Write + ?Sized,
+    {
+        self.render_checked(html, w).map_err(|_| fmt::Error)
+    }
+
+    /// Render `html` to `w`, returning the misplaced content error if that is why rendering failed
+    fn render_checked<T, W>(&self, html: &T, w: &mut W) -> Result<(), Option<VoidElementError>>
+    where
+        T: Render + ?Sized,
+        W: Write + ?Sized,
+    {
+        // `&mut W` is sized even when `W` is not, so it can be erased to `dyn Write` for unsized `T`
+        let mut w = w;
+        let mut f = Formatter::new(&mut w as &mut dyn Write, *self);
+        html.render_dyn(&mut f).map_err(|fmt::Error| f.error.take())
+    }
+
+    /// Render `html` by writing UTF-8 bytes to the given [`io::Write`] sink
+    ///
+    /// Unlike [`fmt::Error`] the returned error tells what went wrong: the I/O error of the sink, or an
+    /// [`io::ErrorKind::InvalidInput`] error wrapping a [`VoidElementError`] for content placed in a void element.
+    pub fn render_to_io<T, W>(&self, html: &T, w: &mut W) -> io::Result<()>
+    where
+        T: Render + ?Sized,
+        W: io::Write + ?Sized,
+    {
+        let mut adapter = IoAdapter {
+            inner: w,
+            error: None,
+        };
+        match self.render_checked(html, &mut adapter) {
+            Ok(()) => Ok(()),
+            Err(error) => Err(match (adapter.error, error) {
+                (Some(e), _) => e,
+                (None, Some(e)) => io::Error::new(io::ErrorKind::InvalidInput, e),
+                (None, None) => io::Error::other("formatter error"),
+            }),
+        }
+    }
+}
+
+/// The output sink of a [`Render`] pass along with the [`Renderer`] settings in use
+///
+/// It implements [`fmt::Write`], so custom [`Render`] implementations can use `write!` on it directly.
+/// Text written that way is not escaped, see [`Formatter::write_text`].
+pub struct Formatter<'w, W: Write + ?Sized> {
+    out: &'w mut W,
+    renderer: Renderer,
+    depth: usize,
+    verbatim: bool,
+    block: bool,
+    error: Option<VoidElementError>,
+}
+
+impl<'w, W: Write + ?Sized> Formatter<'w, W> {
+    /// Create a formatter writing to `out` with the given settings
+    pub fn new(out: &'w mut W, renderer: Renderer) -> Self {
+        Self {
+            out,
+            renderer,
+            depth: 0,
+            verbatim: false,
+            block: true,
+            error: None,
+        }
+    }
+
+    /// The settings used by this formatter
+    pub fn renderer(&self) -> &Renderer {
+        &self.renderer
+    }
+
+    /// Write `text` escaping `&`, `<` and `>`
+    pub fn write_text(&mut self, text: &str) -> fmt::Result {
+        escape::text(self.out, text)
+    }
+
+    /// Write `value` using its [`fmt::Display`] implementation, escaping `&`, `<` and `>` in the output
+    pub fn write_display<T: fmt::Display + ?Sized>(&mut self, value: &T) -> fmt::Result {
+        let mut out = escape::Escaped {
+            out: self.out,
+            escape: escape::text,
+        };
+        write!(out, "{value}")
+    }
+
+    /// Write `value` escaping `&` and `"` so it can be placed in a double quoted attribute
+    pub(crate) fn write_attr_value(&mut self, value: &str) -> fmt::Result {
+        match self.renderer.xml {
+            true => escape::xml_attr(self.out, value),
+            false => escape::attr(self.out, value),
+        }
+    }
+
+    /// Write `value` using its [`fmt::Display`] implementation, escaped like [`Formatter::write_attr_value`]
+    pub(crate) fn write_attr_display<T: fmt::Display + ?Sized>(
+        &mut self,
+        value: &T,
+    ) -> fmt::Result {
+        let mut out = escape::Escaped {
+            out: self.out,
+            escape: match self.renderer.xml {
+                true => escape::xml_attr,
+                false => escape::attr,
+            },
+        };
+        write!(out, "{value}")
+    }
+
+    /// Write `text` so it cannot close the comment it is placed in
+    pub(crate) fn write_comment(&mut self, text: &str) -> fmt::Result {
+        escape::comment(self.out, text)
+    }
+
+    /// Whether the output must be well-formed XML
+    pub(crate) fn is_xml(&self) -> bool {
+        self.renderer.xml
+    }
+
+    /// Whether children may currently be placed on separate indented lines
+    pub(crate) fn is_pretty(&self) -> bool {
+        self.renderer.indent.is_some() && !self.verbatim
+    }
+
+    /// Whether the node being rendered is placed on its own line, as the top level and the children of block layouts are
+    ///
+    /// Sequences spread their nodes over several lines only then, since new lines in inline content add visible spaces.
+    pub(crate) fn is_block(&self) -> bool {
+        self.block
+    }
+
+    /// Run `f` for children laid out on separate lines (`block`) or inline
+    pub(crate) fn layout(
+        &mut self,
+        block: bool,
+        f: impl FnOnce(&mut Self) -> fmt::Result,
+    ) -> fmt::Result {
+        let prev = std::mem::replace(&mut self.block, block);
+        let res = f(self);
+        self.block = prev;
+        res
+    }
+
+    /// Fail the render because the void element or self-closing tag `name` has content, keeping the name for the error
+    pub(crate) fn misplaced_content(&mut self, name: &str) -> fmt::Result {
+        self.error = Some(VoidElementError { name: name.into() });
+        Err(fmt::Error)
+    }
+
+    /// Start a new line at the current indentation level
+    pub(crate) fn newline(&mut self) -> fmt::Result {
+        let width = self.renderer.indent.unwrap_or_default() * self.depth;
+        write!(self.out, "\n{:width$}", "")
+    }
+
+    /// Run `f` one indentation level deeper
+    pub(crate) fn indented(&mut self, f: impl FnOnce(&mut Self) -> fmt::Result) -> fmt::Result {
+        self.depth += 1;
+        let res = f(self);
+        self.depth -= 1;
+        res
+    }
+
+    /// Run `f` without inserting any whitespace, for the contents of whitespace-sensitive elements
+    pub(crate) fn verbatim(&mut self, f: impl FnOnce(&mut Self) -> fmt::Result) -> fmt::Result {
+        let prev = std::mem::replace(&mut self.verbatim, true);
+        let res = f(self);
+        self.verbatim = prev;
+        res
+    }
+}
+
+impl<W: Write + ?Sized> Write for Formatter<'_, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.out.write_str(s)
+    }
+
+    fn write_char(&mut self, c: char) -> fmt::Result {
+        self.out.write_char(c)
+    }
+}
+
+/// Forwards [`fmt::Write`] calls to an [`io::Write`] sink, keeping the first I/O error since [`fmt::Error`] cannot carry it
+struct IoAdapter<'w, W: io::Write + ?Sized> {
+    inner: &'w mut W,
+    error: Option<io::Error>,
+}
+
+impl<W: io::Write + ?Sized> Write for IoAdapter<'_, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.inner.write_all(s.as_bytes()).map_err(|e| {
+            self.error = Some(e);
+            fmt::Error
+        })
+    }
+}
+
+/// Elements around and between which whitespace never affects rendering
+const BLOCK_ELEMENTS: &[&str] = &[
+    "address",
+    "article",
+    "aside",
+    "base",
+    "blockquote",
+    "body",
+    "caption",
+    "col",
+    "colgroup",
+    "dd",
+    "details",
+    "dialog",
+    "div",
+    "dl",
+    "dt",
+    "fieldset",
+    "figcaption",
+    "figure",
+    "footer",
+    "form",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "head",
+    "header",
+    "hgroup",
+    "hr",
+    "html",
+    "li",
+    "link",
+    "main",
+    "menu",
+    "meta",
+    "nav",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "summary",
+    "table",
+    "tbody",
+    "td",
+    "tfoot",
+    "th",
+    "thead",
+    "title",
+    "tr",
+    "ul",
+];
+
+/// Elements that cannot have any content and are written without an end tag
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source",
+    "track", "wbr",
+];
+
+/// Elements whose content must be written exactly as it is
+const WHITESPACE_SENSITIVE_ELEMENTS: &[&str] = &["pre", "textarea", "script", "style"];
+
+/// Attributes whose presence alone means `true`, written as `name="name"` in XHTML
+const BOOLEAN_ATTRIBUTES: &[&str] = &[
+    "allowfullscreen",
+    "async",
+    "autofocus",
+    "autoplay",
+    "checked",
+    "controls",
+    "default",
+    "defer",
+    "disabled",
+    "formnovalidate",
+    "hidden",
+    "inert",
+    "ismap",
+    "itemscope",
+    "loop",
+    "multiple",
+    "muted",
+    "nomodule",
+    "novalidate",
+    "open",
+    "playsinline",
+    "readonly",
+    "required",
+    "reversed",
+    "selected",
+];
+
+/// Whether `name` is a block-level element, unknown elements are treated as inline
+pub(crate) fn is_block_element(name: &str) -> bool {
+    BLOCK_ELEMENTS.iter().any(|e| e.eq_ignore_ascii_case(name))
+}
+
+/// Whether `name` is an HTML5 void element, e.g. `br` or `img`
+pub(crate) fn is_void_element(name: &str) -> bool {
+    VOID_ELEMENTS.iter().any(|e| e.eq_ignore_ascii_case(name))
+}
+
+/// Whether whitespace inside the element `name` is significant
+pub(crate) fn is_whitespace_sensitive(name: &str) -> bool {
+    WHITESPACE_SENSITIVE_ELEMENTS
+        .iter()
+        .any(|e| e.eq_ignore_ascii_case(name))
+}
+
+/// Whether `name` can be written as an attribute name as is
+///
+/// Names are not escaped, so whitespace, quotes, `<`, `>`, `/`, `=` and control characters would let the name end the
+/// attribute or the tag early.
+pub(crate) fn is_valid_attribute_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.chars().any(|c| {
+            c.is_whitespace() || c.is_control() || matches!(c, '"' | '\'' | '<' | '>' | '/' | '=')
+        })
+}
+
+/// Whether `name` is an HTML boolean attribute, e.g. `checked` or `disabled`
+pub(crate) fn is_boolean_attribute(name: &str) -> bool {
+    BOOLEAN_ATTRIBUTES
+        .iter()
+        .any(|e| e.eq_ignore_ascii_case(name))
+}
@@ -0,0 +1,633 @@
+//! Querying [`Html`] trees with CSS selectors
+use std::error::Error;
+use std::fmt;
+
+use crate::{Content, Html, Tag};
+
+/// A parsed CSS selector that can be matched against [`Html`] trees
+///
+/// The supported subset of CSS selectors:
+/// - type (`a`, case-insensitive) and universal (`*`) selectors
+/// - class (`.active`) and id (`#main`) selectors
+/// - attribute selectors: `[href]`, `[type=text]`, `[rel~=noopener]`, `[href^="https"]`, `[href$=".pdf"]` and `[href*=docs]`
+/// - `:nth-child(an+b)` (including `odd` and `even`), `:first-child` and `:last-child`
+/// - descendant (`nav a`) and child (`nav > a`) combinators
+/// - selector lists (`h1, h2`)
+///
+/// Only tags are matched and counted as siblings, comments, text and raw markup are skipped.
+///
+/// ## Example
+/// ```
+/// use hteaml::{hteaml, Selector};
+/// let html = hteaml! {
+///     (nav (a href:"/" = "Home") (a class:active href:"/blog" = "Blog"))
+/// };
+/// let active = Selector::parse("nav > a.active")?;
+/// let links: Vec<_> = active.select(&html).iter().filter_map(|a| a.get_attr("href")).collect();
+/// assert_eq!(links, ["/blog"]);
+/// # Ok::<(), hteaml::SelectorError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Selector {
+    alternatives: Vec<Complex>,
+}
+
+/// The error returned when parsing an invalid or unsupported selector
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelectorError {
+    message: &'static str,
+    position: usize,
+}
+
+impl SelectorError {
+    /// Description of what went wrong
+    pub fn message(&self) -> &str {
+        self.message
+    }
+
+    /// The byte offset in the selector at which the error occurred
+    pub fn position(&self) -> usize {
+        self.position
+    }
+}
+
+impl fmt::Display for SelectorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position)
+    }
+}
+
+impl Error for SelectorError {}
+
+/// Compound selectors joined by combinators, e.g. `nav > a.active`
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Complex {
+    /// The first compound has no combinator, every other one is joined to the previous compound with its combinator
+    parts: Vec<(Combinator, Compound)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Combinator {
+    Descendant,
+    Child,
+}
+
+/// Simple selectors that must all match the same tag, e.g. `a.active[href]`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Compound {
+    name: Option<String>,
+    filters: Vec<Filter>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Filter {
+    Class(String),
+    Id(String),
+    Attr(String, Option<(AttrOp, String)>),
+    NthChild { a: i64, b: i64 },
+    LastChild,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AttrOp {
+    Equals,
+    Includes,
+    Prefix,
+    Suffix,
+    Contains,
+}
+
+/// A tag on the path from the root to the tag being matched
+#[derive(Clone, Copy)]
+struct Step<'r, 'a> {
+    tag: &'r Tag<'a>,
+    /// 1-based position among sibling tags
+    index: usize,
+    siblings: usize,
+}
+
+impl Selector {
+    /// Parse a selector
+    pub fn parse(selector: &str) -> Result<Self, SelectorError> {
+        SelectorParser {
+            input: selector,
+            pos: 0,
+        }
+        .parse()
+    }
+
+    /// All the tags in `html` that match this selector, in depth-first order
+    pub fn select<'r, 'a>(&self, html: &'r Html<'a>) -> Vec<&'r Tag<'a>> {
+        let mut out = vec![];
+        self.walk(&elements(std::iter::once(html)), &mut vec![], &mut |t| {
+            out.push(t);
+            true
+        });
+        out
+    }
+
+    /// The first tag in `html` (in depth-first order) that matches this selector
+    pub fn select_first<'r, 'a>(&self, html: &'r Html<'a>) -> Option<&'r Tag<'a>> {
+        let mut first = None;
+        self.walk(&elements(std::iter::once(html)), &mut vec![], &mut |t| {
+            first = Some(t);
+            false
+        });
+        first
+    }
+
+    /// All the tags nested in `tag` that match this selector, `tag` itself takes part in matching as their ancestor
+    fn select_in<'r, 'a>(&self, tag: &'r Tag<'a>, first_only: bool) -> Vec<&'r Tag<'a>> {
+        let mut out = vec![];
+        let mut path = vec![Step {
+            tag,
+            index: 1,
+            siblings: 1,
+        }];
+        self.walk(&children(tag), &mut path, &mut |t| {
+            out.push(t);
+            !first_only
+        });
+        out
+    }
+
+    /// Visit the tags matching this selector among `tags` and their descendants until `found` returns false
+    fn walk<'r, 'a>(
+        &self,
+        tags: &[&'r Tag<'a>],
+        path: &mut Vec<Step<'r, 'a>>,
+        found: &mut dyn FnMut(&'r Tag<'a>) -> bool,
+    ) -> bool {
+        for (i, &tag) in tags.iter().enumerate() {
+            path.push(Step {
+                tag,
+                index: i + 1,
+                siblings: tags.len(),
+            });
+            let keep_going =
+                (!self.matches(path) || found(tag)) && self.walk(&children(tag), path, found);
+            path.pop();
+            if !keep_going {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Whether the last tag in `path` matches
+    fn matches(&self, path: &[Step]) -> bool {
+        self.alternatives
+            .iter()
+            .any(|c| c.matches(c.parts.len() - 1, path))
+    }
+}
+
+impl Complex {
+    /// Whether the compound at `part` matches the last tag in `path` with all the previous compounds matching its ancestors
+    fn matches(&self, part: usize, path: &[Step]) -> bool {
+        let Some((step, ancestors)) = path.split_last() else {
+            return false;
+        };
+        let (combinator, compound) = &self.parts[part];
+        if !compound.matches(step) {
+            return false;
+        }
+        if part == 0 {
+            return true;
+        }
+        match combinator {
+            Combinator::Child => self.matches(part - 1, ancestors),
+            Combinator::Descendant => {
+                (1..=ancestors.len()).any(|end| self.matches(part - 1, &ancestors[..end]))
+            }
+        }
+    }
+}
+
+impl Compound {
+    fn matches(&self, step: &Step) -> bool {
+        let tag = step.tag;
+        if let Some(name) = &self.name {
+            if !tag.name().eq_ignore_ascii_case(name) {
+                return false;
+            }
+        }
+        self.filters.iter().all(|filter| match filter {
+            Filter::Class(class) => tag.has_class(class),
+            Filter::Id(id) => tag.get_attr("id").as_deref() == Some(id),
+            Filter::Attr(key, None) => tag
+                .attributes()
+                .iter()
+                .any(|a| a.key().eq_ignore_ascii_case(key)),
+            Filter::Attr(key, Some((op, expected))) => tag
+                .attributes()
+                .iter()
+                .filter(|a| a.key().eq_ignore_ascii_case(key))
+                .any(|a| {
+                    let val = a.value().to_str();
+                    match op {
+                        AttrOp::Equals => val == expected.as_str(),
+                        AttrOp::Includes => val.split_ascii_whitespace().any(|v| v == expected),
+                        AttrOp::Prefix => {
+                            !expected.is_empty() && val.starts_with(expected.as_str())
+                        }
+                        AttrOp::Suffix => !expected.is_empty() && val.ends_with(expected.as_str()),
+                        AttrOp::Contains => !expected.is_empty() && val.contains(expected.as_str()),
+                    }
+                }),
+            Filter::NthChild { a, b } => {
+                // the position must be a*n + b for some n >= 0
+                let diff = step.index as i64 - b;
+                match a {
+                    0 => diff == 0,
+                    a => diff % a == 0 && diff / a >= 0,
+                }
+            }
+            Filter::LastChild => step.index == step.siblings,
+        })
+    }
+}
+
+/// The tags among `nodes`, looking through [`Html::Html`] sequences
+fn elements<'r, 'a>(nodes: impl Iterator<Item = &'r Html<'a>>) -> Vec<&'r Tag<'a>> {
+    let mut out = vec![];
+    nodes.for_each(|n| collect_elements(n, &mut out));
+    out
+}
+
+fn collect_elements<'r, 'a>(node: &'r Html<'a>, out: &mut Vec<&'r Tag<'a>>) {
+    match node {
+        Html::Tag(t) => out.push(t),
+        Html::Html(seq) => seq.iter().for_each(|n| collect_elements(n, out)),
+        _ => {}
+    }
+}
+
+/// The child tags of `tag`
+fn children<'r, 'a>(tag: &'r Tag<'a>) -> Vec<&'r Tag<'a>> {
+    elements(tag.children().iter().filter_map(Content::as_html))
+}
+
+impl<'a> Html<'a> {
+    /// All the tags in this tree that match the CSS `selector`, in depth-first order
+    ///
+    /// See [`Selector`] for the supported syntax. To run the same selector many times parse it once with [`Selector::parse`].
+    ///
+    /// ## Example
+    /// ```
+    /// use hteaml::hteaml;
+    /// let html = hteaml! {
+    ///     (ul (li = "one") (li = "two") (li = "three"))
+    /// };
+    /// let second = html.select_first("ul > li:nth-child(2)")?;
+    /// assert_eq!(second.and_then(|li| li.children()[0].as_str()), Some("two"));
+    /// assert_eq!(html.select("li:nth-child(odd)")?.len(), 2);
+    /// # Ok::<(), hteaml::SelectorError>(())
+    /// ```
+    pub fn select(&self, selector: &str) -> Result<Vec<&Tag<'a>>, SelectorError> {
+        Ok(Selector::parse(selector)?.select(self))
+    }
+
+    /// The first tag in this tree (in depth-first order) that matches the CSS `selector`
+    pub fn select_first(&self, selector: &str) -> Result<Option<&Tag<'a>>, SelectorError> {
+        Ok(Selector::parse(selector)?.select_first(self))
+    }
+}
+
+impl<'a> Tag<'a> {
+    /// All the tags nested in this tag that match the CSS `selector`, in depth-first order
+    ///
+    /// The tag itself is not matched but it is taken into account as an ancestor, so `nav > a` selects the links
+    /// directly inside of a `nav` tag.
+    pub fn select(&self, selector: &str) -> Result<Vec<&Tag<'a>>, SelectorError> {
+        Ok(Selector::parse(selector)?.select_in(self, false))
+    }
+
+    /// The first tag nested in this tag (in depth-first order) that matches the CSS `selector`
+    pub fn select_first(&self, selector: &str) -> Result<Option<&Tag<'a>>, SelectorError> {
+        Ok(Selector::parse(selector)?
+            .select_in(self, true)
+            .into_iter()
+            .next())
+    }
+}
+
+struct SelectorParser<'s> {
+    input: &'s str,
+    pos: usize,
+}
+
+impl SelectorParser<'_> {
+    fn parse(mut self) -> Result<Selector, SelectorError> {
+        let mut alternatives = vec![self.complex()?];
+        while self.eat(',') {
+            alternatives.push(self.complex()?);
+        }
+        Ok(Selector { alternatives })
+    }
+
+    fn error(&self, message: &'static str) -> SelectorError {
+        SelectorError {
+            message,
+            position: self.pos,
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.input[self.pos..].chars().next()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        self.skip_whitespace();
+        let ate = self.peek() == Some(c);
+        if ate {
+            self.pos += c.len_utf8();
+        }
+        ate
+    }
+
+    fn skip_whitespace(&mut self) -> bool {
+        let rest = &self.input[self.pos..];
+        let trimmed = rest.trim_start();
+        self.pos += rest.len() - trimmed.len();
+        rest.len() != trimmed.len()
+    }
+
+    fn complex(&mut self) -> Result<Complex, SelectorError> {
+        self.skip_whitespace();
+        let mut parts = vec![(Combinator::Descendant, self.compound()?)];
+        loop {
+            let had_whitespace = self.skip_whitespace();
+            let combinator = match self.peek() {
+                None | Some(',') => break,
+                Some('>') => {
+                    self.pos += 1;
+                    self.skip_whitespace();
+                    Combinator::Child
+                }
+                Some('+' | '~') => return Err(self.error("sibling combinators are not supported")),
+                Some(_) if had_whitespace => Combinator::Descendant,
+                Some(_) => return Err(self.error("unexpected character")),
+            };
+            parts.push((combinator, self.compound()?));
+        }
+        Ok(Complex { parts })
+    }
+
+    fn compound(&mut self) -> Result<Compound, SelectorError> {
+        let mut compound = Compound::default();
+        let universal = match self.peek() {
+            Some('*') => {
+                self.pos += 1;
+                true
+            }
+            Some(c) if is_ident_char(c) => {
+                compound.name = Some(self.ident()?);
+                false
+            }
+            _ => false,
+        };
+        loop {
+            let filter = match self.peek() {
+                Some('.') => {
+                    self.pos += 1;
+                    Filter::Class(self.ident()?)
+                }
+                Some('#') => {
+                    self.pos += 1;
+                    Filter::Id(self.ident()?)
+                }
+                Some('[') => {
+                    self.pos += 1;
+                    self.attribute()?
+                }
+                Some(':') => {
+                    self.pos += 1;
+                    self.pseudo_class()?
+                }
+                _ => break,
+            };
+            compound.filters.push(filter);
+        }
+        if !universal && compound.name.is_none() && compound.filters.is_empty() {
+            return Err(self.error("expected a selector"));
+        }
+        Ok(compound)
+    }
+
+    fn ident(&mut self) -> Result<String, SelectorError> {
+        let rest = &self.input[self.pos..];
+        let len = rest.find(|c| !is_ident_char(c)).unwrap_or(rest.len());
+        if len == 0 {
+            return Err(self.error("expected a name"));
+        }
+        self.pos += len;
+        Ok(rest[..len].to_owned())
+    }
+
+    fn attribute(&mut self) -> Result<Filter, SelectorError> {
+        self.skip_whitespace();
+        let key = self.ident()?;
+        self.skip_whitespace();
+        let rest = &self.input[self.pos..];
+        let op = [
+            ("=", AttrOp::Equals),
+            ("~=", AttrOp::Includes),
+            ("^=", AttrOp::Prefix),
+            ("$=", AttrOp::Suffix),
+            ("*=", AttrOp::Contains),
+        ]
+        .into_iter()
+        .find(|(token, _)| rest.starts_with(token));
+        let Some((token, op)) = op else {
+            return match self.eat(']') {
+                true => Ok(Filter::Attr(key, None)),
+                false => Err(self.error("expected `]` or an attribute operator")),
+            };
+        };
+        self.pos += token.len();
+        self.skip_whitespace();
+        let value = match self.peek() {
+            Some(quote @ ('"' | '\'')) => {
+                let rest = &self.input[self.pos + 1..];
+                let len = rest
+                    .find(quote)
+                    .ok_or_else(|| self.error("unterminated string"))?;
+                self.pos += len + 2;
+                rest[..len].to_owned()
+            }
+            _ => self.ident()?,
+        };
+        match self.eat(']') {
+            true => Ok(Filter::Attr(key, Some((op, value)))),
+            false => Err(self.error("expected `]`")),
+        }
+    }
+
+    fn pseudo_class(&mut self) -> Result<Filter, SelectorError> {
+        let start = self.pos;
+        match self.ident()?.to_ascii_lowercase().as_str() {
+            "first-child" => Ok(Filter::NthChild { a: 0, b: 1 }),
+            "last-child" => Ok(Filter::LastChild),
+            "nth-child" => {
+                if !self.eat('(') {
+                    return Err(self.error("expected `(`"));
+                }
+                let rest = &self.input[self.pos..];
+                let len = rest.find(')').ok_or_else(|| self.error("expected `)`"))?;
+                let (a, b) = parse_nth(&rest[..len])
+                    .ok_or_else(|| self.error("invalid :nth-child argument"))?;
+                self.pos += len + 1;
+                Ok(Filter::NthChild { a, b })
+            }
+            _ => {
+                self.pos = start;
+                Err(self.error("unsupported pseudo-class"))
+            }
+        }
+    }
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '-' || c == '_'
+}
+
+/// Parse the `an+b` argument of `:nth-child`
+fn parse_nth(arg: &str) -> Option<(i64, i64)> {
+    let arg: String = arg.chars().filter(|c| !c.is_whitespace()).collect();
+    let arg = arg.to_ascii_lowercase();
+    match arg.as_str() {
+        "odd" => return Some((2, 1)),
+        "even" => return Some((2, 0)),
+        _ => {}
+    }
+    let Some((a, b)) = arg.split_once('n') else {
+        return Some((0, arg.parse().ok()?));
+    };
+    let a = match a {
+        "" | "+" => 1,
+        "-" => -1,
+        a => a.parse().ok()?,
+    };
+    let b = match b {
+        "" => 0,
+        b if b.starts_with(['+', '-']) => b.parse().ok()?,
+        _ => return None,
+    };
+    Some((a, b))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{hteaml, Html, Selector, SelectorError, Str, Tag};
+
+    fn page() -> Html<'static> {
+        hteaml! {
+            (header (nav
+                (a href:"/" = "Home")
+                (a class:"link active" href:"/blog" = "Blog")
+                (span (a href:"https://example.com/docs.pdf" rel:"noopener external" = "Docs"))
+            ))
+            (main id:content
+                (ul (li = "1") (li = "2") (li = "3") (li = "4") (li = "5"))
+            )
+        }
+    }
+
+    fn hrefs<'r>(tags: &[&'r Tag]) -> Vec<Str<'r>> {
+        tags.iter().filter_map(|t| t.get_attr("href")).collect()
+    }
+
+    fn texts<'r>(tags: &[&'r Tag]) -> Vec<&'r str> {
+        tags.iter()
+            .filter_map(|t| t.children().first()?.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn simple_selectors() -> Result<(), SelectorError> {
+        let page = page();
+        assert_eq!(page.select("a")?.len(), 3);
+        assert_eq!(page.select("*")?.len(), 13);
+        assert_eq!(hrefs(&page.select(".active")?), ["/blog"]);
+        assert_eq!(hrefs(&page.select("a.link.active")?), ["/blog"]);
+        assert_eq!(page.select("#content")?.len(), 1);
+        assert_eq!(page.select("main#content.x")?.len(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn attribute_selectors() -> Result<(), SelectorError> {
+        let page = page();
+        assert_eq!(page.select("[href]")?.len(), 3);
+        assert_eq!(hrefs(&page.select(r#"a[href="/"]"#)?), ["/"]);
+        assert_eq!(page.select("[rel~=noopener]")?.len(), 1);
+        assert_eq!(page.select("[rel~=noop]")?.len(), 0);
+        assert_eq!(page.select("[href^='https']")?.len(), 1);
+        assert_eq!(page.select("[href$=\".pdf\"]")?.len(), 1);
+        assert_eq!(page.select("[href*=example]")?.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn combinators() -> Result<(), SelectorError> {
+        let page = page();
+        assert_eq!(page.select("nav a")?.len(), 3);
+        assert_eq!(hrefs(&page.select("nav > a")?), ["/", "/blog"]);
+        assert_eq!(page.select("header > a")?.len(), 0);
+        assert_eq!(page.select("header span > a")?.len(), 1);
+        assert_eq!(page.select("a, li")?.len(), 8);
+        Ok(())
+    }
+
+    #[test]
+    fn nth_child() -> Result<(), SelectorError> {
+        let page = page();
+        assert_eq!(texts(&page.select("li:nth-child(2)")?), ["2"]);
+        assert_eq!(texts(&page.select("li:nth-child(odd)")?), ["1", "3", "5"]);
+        assert_eq!(texts(&page.select("li:nth-child(even)")?), ["2", "4"]);
+        assert_eq!(texts(&page.select("li:nth-child(3n+1)")?), ["1", "4"]);
+        assert_eq!(texts(&page.select("li:nth-child(-n+2)")?), ["1", "2"]);
+        assert_eq!(
+            texts(&page.select("li:first-child, li:last-child")?),
+            ["1", "5"]
+        );
+        assert_eq!(hrefs(&page.select("nav > :nth-child(2)")?), ["/blog"]);
+        Ok(())
+    }
+
+    #[test]
+    fn first_and_scoped() -> Result<(), SelectorError> {
+        let page = page();
+        assert_eq!(page.select_first("li")?.map(|t| t.name()), Some("li"));
+        assert_eq!(page.select_first("table")?, None);
+        let nav = page.select_first("nav")?;
+        assert_eq!(
+            nav.map(|n| hrefs(&n.select("nav > a").unwrap_or_default())),
+            Some(vec![Str::from("/"), Str::from("/blog")])
+        );
+        assert_eq!(
+            nav.and_then(|n| n.select_first("a").ok().flatten())
+                .and_then(|a| a.get_attr("href"))
+                .as_deref(),
+            Some("/")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_selectors() {
+        for sel in [
+            "",
+            "a >",
+            "a + b",
+            "[href",
+            ":hover",
+            "li:nth-child(x)",
+            "a,",
+        ] {
+            assert!(Selector::parse(sel).is_err(), "{sel}");
+        }
+        assert_eq!(Selector::parse("a:hover").map_err(|e| e.position()), Err(2));
+    }
+}
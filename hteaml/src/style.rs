@@ -0,0 +1,193 @@
+//! Inline CSS declarations for the `style` attribute
+use std::fmt::{self, Display};
+
+use crate::{AttrValue, IntoAttr, IntoStr, Str};
+
+/// The declarations of a `style` attribute, kept in the order they were added
+///
+/// Values accept the types accepted by [`Tag::attr`](crate::Tag::attr), so `None` values leave the declaration out.
+/// The whole attribute is escaped when rendered like any other attribute value, and an empty style leaves the attribute
+/// out. The [`hteaml`](crate::hteaml) macro builds one from the `style:{ color: "red", margin-top: {top} }` syntax.
+///
+/// HTML escaping doesn't stop a value from ending its declaration and starting others, so declarations that could
+/// are left out: names that aren't made of letters, digits, `-` and `_`, and values containing `;`, `{` or `}` outside
+/// of a quoted string, or an unterminated string. Values are otherwise written as they are and still have to be
+/// trusted CSS, a `url(...)` for instance is not checked.
+///
+/// ## Example
+/// ```
+/// use hteaml::{Render, Style, Tag};
+/// let width: Option<u32> = None;
+/// let style = Style::new()
+///     .prop("color", "red")
+///     .prop("width", width)
+///     .prop("font-family", r#""Fira Sans", sans-serif"#);
+/// assert_eq!(style.to_string(), r#"color: red; font-family: "Fira Sans", sans-serif"#);
+///
+/// let tag = Tag::new("p").attr("style", style);
+/// assert_eq!(
+///     tag.render(),
+///     Ok(r#"<p style="color: red; font-family: &quot;Fira Sans&quot;, sans-serif"></p>"#.into())
+/// );
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Style<'a>(Vec<(Str<'a>, AttrValue<'a>)>);
+
+impl<'a> Style<'a> {
+    /// Create a style without declarations
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the declaration `name: value`, or nothing if `value` is `None`
+    pub fn prop<A, B>(mut self, name: A, value: B) -> Self
+    where
+        A: IntoStr<'a>,
+        B: IntoAttr<'a>,
+    {
+        self.push(name, value);
+        self
+    }
+
+    /// Add the declaration `name: value`, the in-place counterpart of [`Style::prop`]
+    ///
+    /// The declaration is left out if it could inject other declarations, see [`Style`].
+    pub fn push<A, B>(&mut self, name: A, value: B)
+    where
+        A: IntoStr<'a>,
+        B: IntoAttr<'a>,
+    {
+        let name = name.into_str();
+        if let Some(value) = value.into_attr() {
+            if is_property_name(&name) && is_contained_value(&value.to_str()) {
+                self.0.push((name, value));
+            }
+        }
+    }
+
+    /// Whether there are no declarations
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The declarations as `(name, value)` pairs in the order they were added
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &AttrValue<'a>)> {
+        self.0.iter().map(|(name, value)| (&**name, value))
+    }
+}
+
+impl Display for Style<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.iter().enumerate().try_for_each(|(i, (name, value))| {
+            let sep = if i == 0 { "" } else { "; " };
+            write!(f, "{sep}{name}: {value}")
+        })
+    }
+}
+
+impl<'a, A: IntoStr<'a>, B: IntoAttr<'a>> FromIterator<(A, B)> for Style<'a> {
+    fn from_iter<I: IntoIterator<Item = (A, B)>>(iter: I) -> Self {
+        let mut style = Self::new();
+        style.extend(iter);
+        style
+    }
+}
+
+impl<'a, A: IntoStr<'a>, B: IntoAttr<'a>> Extend<(A, B)> for Style<'a> {
+    fn extend<I: IntoIterator<Item = (A, B)>>(&mut self, iter: I) {
+        iter.into_iter()
+            .for_each(|(name, value)| self.push(name, value));
+    }
+}
+
+/// Whether `name` is a plain property name, custom properties such as `--gap` included
+fn is_property_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Whether `value` stays within its declaration: no `;`, `{` or `}` outside of strings, and no unterminated string
+fn is_contained_value(value: &str) -> bool {
+    let mut quote = None;
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (_, '\\') => {
+                chars.next();
+            }
+            (Some(_), '\n' | '\r' | '\x0c') => return false,
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, ';' | '{' | '}') => return false,
+            (None, _) => {}
+        }
+    }
+    quote.is_none()
+}
+
+/// The declarations are written one after another when rendered, see [`AttrValue::Concat`]
+impl<'a> IntoAttr<'a> for Style<'a> {
+    fn into_attr(self) -> Option<AttrValue<'a>> {
+        if self.is_empty() {
+            return None;
+        }
+        let mut parts = Vec::with_capacity(self.0.len() * 4);
+        for (i, (name, value)) in self.0.into_iter().enumerate() {
+            if i > 0 {
+                parts.push(AttrValue::Str("; ".into()));
+            }
+            parts.push(AttrValue::Str(name));
+            parts.push(AttrValue::Str(": ".into()));
+            parts.push(value);
+        }
+        Some(AttrValue::Concat(parts))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Renderer, Style, Tag};
+
+    #[test]
+    fn declarations_in_order() {
+        let style: Style = [
+            ("margin", Some("0")),
+            ("color", None),
+            ("margin-top", Some("4px")),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(style.to_string(), "margin: 0; margin-top: 4px");
+        let tag = Tag::new("div").attr("style", style.prop("opacity", 0.5));
+        assert_eq!(
+            Renderer::new().xml().render(&tag),
+            Ok(r#"<div style="margin: 0; margin-top: 4px; opacity: 0.5" />"#.into())
+        );
+    }
+
+    #[test]
+    fn injected_declarations_are_left_out() {
+        let user = "red; background: url(javascript:alert(1))";
+        let style = Style::new()
+            .prop("color", user)
+            .prop("width", "1px}body{color:red")
+            .prop("font-family", r#""Fira; Sans", 'it\'s'"#)
+            .prop("content", r#""open"#)
+            .prop("color:red;x", "y")
+            .prop("--gap", "2px");
+        assert_eq!(
+            style.to_string(),
+            r#"font-family: "Fira; Sans", 'it\'s'; --gap: 2px"#
+        );
+    }
+
+    #[test]
+    fn empty_style_is_left_out() {
+        let style = Style::new().prop("color", None::<&str>);
+        assert!(style.is_empty());
+        assert!(!Tag::new("p").attr("style", style).has_attr("style"));
+    }
+}
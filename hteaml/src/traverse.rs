@@ -0,0 +1,80 @@
+//! Depth-first and breadth-first iterators over [`Html`] trees
+use std::collections::VecDeque;
+
+use crate::{Content, Html};
+
+/// Depth-first (pre-order) iterator over the nodes of an [`Html`] tree, see [`Html::dfs`]
+#[derive(Debug, Clone)]
+pub struct Dfs<'r, 'a> {
+    /// Nodes left to visit, the next one last
+    stack: Vec<&'r Html<'a>>,
+}
+
+impl<'r, 'a> Dfs<'r, 'a> {
+    pub(crate) fn new(roots: impl DoubleEndedIterator<Item = &'r Html<'a>>) -> Self {
+        let mut stack = vec![];
+        roots.rev().for_each(|root| push_rev(&mut stack, root));
+        Self { stack }
+    }
+}
+
+impl<'r, 'a> Iterator for Dfs<'r, 'a> {
+    type Item = &'r Html<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        children(node)
+            .rev()
+            .for_each(|child| push_rev(&mut self.stack, child));
+        Some(node)
+    }
+}
+
+/// Breadth-first (level-order) iterator over the nodes of an [`Html`] tree, see [`Html::bfs`]
+#[derive(Debug, Clone)]
+pub struct Bfs<'r, 'a> {
+    queue: VecDeque<&'r Html<'a>>,
+}
+
+impl<'r, 'a> Bfs<'r, 'a> {
+    pub(crate) fn new(roots: impl Iterator<Item = &'r Html<'a>>) -> Self {
+        let mut queue = VecDeque::new();
+        roots.for_each(|root| push(&mut queue, root));
+        Self { queue }
+    }
+}
+
+impl<'r, 'a> Iterator for Bfs<'r, 'a> {
+    type Item = &'r Html<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.queue.pop_front()?;
+        children(node).for_each(|child| push(&mut self.queue, child));
+        Some(node)
+    }
+}
+
+/// The nodes nested directly in `node`
+fn children<'r, 'a>(node: &'r Html<'a>) -> impl DoubleEndedIterator<Item = &'r Html<'a>> {
+    let content = match node {
+        Html::Tag(t) => t.content.as_slice(),
+        _ => &[],
+    };
+    content.iter().filter_map(Content::as_html)
+}
+
+/// Push `node` to the back of `queue`, replacing sequences with the nodes in them
+fn push<'r, 'a>(queue: &mut VecDeque<&'r Html<'a>>, node: &'r Html<'a>) {
+    match node {
+        Html::Html(seq) => seq.iter().for_each(|n| push(queue, n)),
+        node => queue.push_back(node),
+    }
+}
+
+/// Push `node` on `stack` in reverse, replacing sequences with the nodes in them so the first one ends up on top
+fn push_rev<'r, 'a>(stack: &mut Vec<&'r Html<'a>>, node: &'r Html<'a>) {
+    match node {
+        Html::Html(seq) => seq.iter().rev().for_each(|n| push_rev(stack, n)),
+        node => stack.push(node),
+    }
+}
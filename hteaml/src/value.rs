@@ -0,0 +1,253 @@
+//! Values that are written into the output while rendering instead of being turned into strings up front
+use std::borrow::Cow;
+use std::fmt::{self, Display};
+
+use crate::{Content, IntoStr, Str};
+
+/// A value that is formatted straight into the output when rendered, such as a number
+///
+/// Integers, floats, `bool` and `char` convert into `Fmt`, and so into [`Content`] and [`AttrValue`], without
+/// allocating a `String` first. Any other type implementing [`Display`] can be used by borrowing it with [`Fmt::display`].
+/// The formatted text is escaped like any other text.
+///
+/// Two values are equal when they format to the same text.
+///
+/// ## Example
+/// ```
+/// use hteaml::{Fmt, Render, Tag};
+/// let price = 9.5;
+/// let tag = Tag::new("td")
+///     .attr("data-qty", 3)
+///     .content(price)
+///     .content(Fmt::display(&std::net::Ipv4Addr::LOCALHOST));
+/// assert_eq!(tag.render(), Ok(r#"<td data-qty="3">9.5127.0.0.1</td>"#.into()));
+/// ```
+#[derive(Clone, Copy)]
+pub enum Fmt<'a> {
+    /// A signed integer
+    Int(i128),
+    /// An unsigned integer
+    Uint(u128),
+    /// A single precision float
+    F32(f32),
+    /// A double precision float
+    F64(f64),
+    /// A boolean, written as `true` or `false`
+    Bool(bool),
+    /// A single character
+    Char(char),
+    /// Any other value, see [`Fmt::display`]
+    Display(&'a (dyn Display + Sync)),
+}
+
+impl<'a> Fmt<'a> {
+    /// Borrow a value that is written using its [`Display`] implementation
+    pub fn display<T: Display + Sync>(value: &'a T) -> Self {
+        Self::Display(value)
+    }
+}
+
+impl Display for Fmt<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Fmt::Int(v) => v.fmt(f),
+            Fmt::Uint(v) => v.fmt(f),
+            Fmt::F32(v) => v.fmt(f),
+            Fmt::F64(v) => v.fmt(f),
+            Fmt::Bool(v) => v.fmt(f),
+            Fmt::Char(v) => v.fmt(f),
+            Fmt::Display(v) => v.fmt(f),
+        }
+    }
+}
+
+impl fmt::Debug for Fmt<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Fmt::Int(_) => "Int",
+            Fmt::Uint(_) => "Uint",
+            Fmt::F32(_) => "F32",
+            Fmt::F64(_) => "F64",
+            Fmt::Bool(_) => "Bool",
+            Fmt::Char(_) => "Char",
+            Fmt::Display(_) => "Display",
+        };
+        f.debug_tuple(name).field(&format_args!("{self}")).finish()
+    }
+}
+
+impl PartialEq for Fmt<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.to_string() == other.to_string()
+    }
+}
+
+impl Eq for Fmt<'_> {}
+
+macro_rules! impl_from {
+    ($($variant:ident($inner:ty): $($t:ty),+;)+) => {$($(
+        impl From<$t> for Fmt<'_> {
+            fn from(value: $t) -> Self {
+                Self::$variant(value as $inner)
+            }
+        }
+
+        impl From<$t> for Content<'_> {
+            fn from(value: $t) -> Self {
+                Self::Fmt(value.into())
+            }
+        }
+
+        impl From<$t> for AttrValue<'_> {
+            fn from(value: $t) -> Self {
+                Self::Fmt(value.into())
+            }
+        }
+    )+)+};
+}
+
+impl_from! {
+    Int(i128): i8, i16, i32, i64, i128, isize;
+    Uint(u128): u8, u16, u32, u64, u128, usize;
+    F32(f32): f32;
+    F64(f64): f64;
+    Bool(bool): bool;
+    Char(char): char;
+}
+
+/// The value of an attribute, either a string, a [`Fmt`] value that is formatted when rendered or a sequence of them
+///
+/// A sequence is written part after part when rendered, without joining the parts into a `String` first. It can be
+/// collected from an iterator of values.
+///
+/// Like [`Fmt`], two values are equal when they render to the same text, whatever variants hold it.
+///
+/// ## Example
+/// ```
+/// use hteaml::{AttrValue, Render, Tag};
+/// let href: AttrValue = [AttrValue::from("/users/"), 42.into(), "/edit".into()].into_iter().collect();
+/// assert_eq!(href.to_str(), "/users/42/edit");
+/// let tag = Tag::new("a").attr("href", href);
+/// assert_eq!(tag.render(), Ok(r#"<a href="/users/42/edit"></a>"#.into()));
+/// ```
+#[derive(Debug, Clone)]
+pub enum AttrValue<'a> {
+    /// A string value
+    Str(Str<'a>),
+    /// A value formatted when rendered, e.g. a number
+    Fmt(Fmt<'a>),
+    /// Values written one after another
+    Concat(Vec<AttrValue<'a>>),
+}
+
+impl<'a> AttrValue<'a> {
+    /// The value if it is a string
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            AttrValue::Str(s) => Some(s),
+            AttrValue::Fmt(_) | AttrValue::Concat(_) => None,
+        }
+    }
+
+    /// The value as text, formatting it if it isn't a string
+    pub fn to_str(&self) -> Str<'_> {
+        match self {
+            AttrValue::Str(s) => Cow::Borrowed(s),
+            v => Cow::Owned(v.to_string()),
+        }
+    }
+
+    /// Convert the value into text, formatting it if it isn't a string
+    pub fn into_str(self) -> Str<'a> {
+        match self {
+            AttrValue::Str(s) => s,
+            v => Cow::Owned(v.to_string()),
+        }
+    }
+
+    /// Whether the value is an empty string, as it is for attributes written without a value
+    ///
+    /// A sequence is empty if all of its parts are.
+    pub fn is_empty(&self) -> bool {
+        match self {
+            AttrValue::Concat(parts) => parts.iter().all(AttrValue::is_empty),
+            v => v.as_str().is_some_and(str::is_empty),
+        }
+    }
+
+    /// Mutable access to the value as an owned string, formatting it first if it isn't a string
+    pub(crate) fn to_mut(&mut self) -> &mut String {
+        match self {
+            AttrValue::Str(s) => s.to_mut(),
+            v => {
+                *v = AttrValue::Str(Cow::Owned(v.to_string()));
+                v.to_mut()
+            }
+        }
+    }
+}
+
+impl Display for AttrValue<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AttrValue::Str(s) => f.write_str(s),
+            AttrValue::Fmt(v) => v.fmt(f),
+            AttrValue::Concat(parts) => parts.iter().try_for_each(|part| part.fmt(f)),
+        }
+    }
+}
+
+impl PartialEq for AttrValue<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.to_str() == other.to_str()
+    }
+}
+
+impl Eq for AttrValue<'_> {}
+
+impl<'a, T: Into<AttrValue<'a>>> FromIterator<T> for AttrValue<'a> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::Concat(iter.into_iter().map(Into::into).collect())
+    }
+}
+
+impl<'a, T: IntoStr<'a>> From<T> for AttrValue<'a> {
+    fn from(value: T) -> Self {
+        Self::Str(value.into_str())
+    }
+}
+
+impl<'a> From<Fmt<'a>> for AttrValue<'a> {
+    fn from(value: Fmt<'a>) -> Self {
+        Self::Fmt(value)
+    }
+}
+
+/// Types that can be given as the value of [`Tag::attr`](crate::Tag::attr)
+///
+/// Every type implementing `Into<AttrValue>` is one, and so is an `Option` of it: `None` leaves the attribute out,
+/// which is how optional attributes such as an `href` are written.
+///
+/// ## Example
+/// ```
+/// use hteaml::{Render, Tag};
+/// let title: Option<&str> = None;
+/// let tag = Tag::new("a").attr("href", Some("/")).attr("title", title);
+/// assert_eq!(tag.render(), Ok(r#"<a href="/"></a>"#.into()));
+/// ```
+pub trait IntoAttr<'a> {
+    /// The attribute value, `None` if the attribute is left out
+    fn into_attr(self) -> Option<AttrValue<'a>>;
+}
+
+impl<'a, T: Into<AttrValue<'a>>> IntoAttr<'a> for T {
+    fn into_attr(self) -> Option<AttrValue<'a>> {
+        Some(self.into())
+    }
+}
+
+impl<'a, T: Into<AttrValue<'a>>> IntoAttr<'a> for Option<T> {
+    fn into_attr(self) -> Option<AttrValue<'a>> {
+        self.map(Into::into)
+    }
+}
@@ -1,4 +1,5 @@
-use hteaml::{hteaml, Html, Render};
+//! Tests for composing pages out of functions returning [`Html`]
+use hteaml::{hteaml, Component, Html, Render};
 
 #[test]
 fn composition() {
@@ -34,13 +35,41 @@ fn composition_advanced() {
     )
 }
 
+#[test]
+fn composition_components() {
+    let message = "Rust macros are very cool!";
+    let page = hteaml! {
+        (@Page title:"composition" (p = {message}))
+    };
+    assert_eq!(
+        page.render(),
+        Ok(format!(
+            "<html><head><title>composition</title></head><body><p>{message}</p><footer>nothing to see here</footer></body></html>"
+        ))
+    )
+}
+
+struct Page<'a> {
+    title: &'a str,
+}
+
+impl<'a> Component<'a> for Page<'a> {
+    fn render(self, children: Html<'a>) -> Html<'a> {
+        hteaml! {
+            (html
+                (head (title = {self.title}))
+                (body = {children} {footer()}))
+        }
+    }
+}
+
 fn head() -> Html<'static> {
     hteaml! {
         (head (title = "composition"))
     }
 }
 
-fn body(text: &str) -> Html {
+fn body(text: &str) -> Html<'_> {
     hteaml! {
         (body = {text} {footer()})
     }